@@ -1,6 +1,12 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure()
         .build_server(false)
+        // Large values are passed around without copying them.
+        .bytes([
+            ".etcdserverpb.PutRequest.value",
+            ".etcdserverpb.RangeRequest.key",
+            ".mvccpb.KeyValue.value",
+        ])
         .compile_protos(
             &[
                 "proto/auth.proto",
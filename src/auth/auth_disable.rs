@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Default, Clone)]
 pub struct AuthDisableRequest {
@@ -17,10 +17,12 @@ pub struct AuthDisableResponse {
     pub header: ResponseHeader,
 }
 
-impl From<etcdserverpb::AuthDisableResponse> for AuthDisableResponse {
-    fn from(proto: etcdserverpb::AuthDisableResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
-        }
+impl TryFrom<etcdserverpb::AuthDisableResponse> for AuthDisableResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthDisableResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
     }
 }
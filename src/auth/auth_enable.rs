@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Default, Clone)]
 pub struct AuthEnableRequest {
@@ -17,10 +17,12 @@ pub struct AuthEnableResponse {
     pub header: ResponseHeader,
 }
 
-impl From<etcdserverpb::AuthEnableResponse> for AuthEnableResponse {
-    fn from(proto: etcdserverpb::AuthEnableResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
-        }
+impl TryFrom<etcdserverpb::AuthEnableResponse> for AuthEnableResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthEnableResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
     }
 }
@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Default, Clone)]
 pub struct AuthStatusRequest {
@@ -19,12 +19,14 @@ pub struct AuthStatusResponse {
     pub auth_revision: u64,
 }
 
-impl From<etcdserverpb::AuthStatusResponse> for AuthStatusResponse {
-    fn from(proto: etcdserverpb::AuthStatusResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::AuthStatusResponse> for AuthStatusResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthStatusResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             enabled: proto.enabled,
             auth_revision: proto.auth_revision,
-        }
+        })
     }
 }
@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct AuthenticateRequest {
@@ -48,11 +48,13 @@ pub struct AuthenticateResponse {
     pub token: String,
 }
 
-impl From<etcdserverpb::AuthenticateResponse> for AuthenticateResponse {
-    fn from(proto: etcdserverpb::AuthenticateResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::AuthenticateResponse> for AuthenticateResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthenticateResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             token: proto.token,
-        }
+        })
     }
 }
@@ -4,6 +4,7 @@ mod auth_status;
 mod authenticate;
 mod role_add;
 mod role_delete;
+mod role_grant_permission;
 mod role_list;
 pub use auth_disable::{AuthDisableRequest, AuthDisableResponse};
 pub use auth_enable::{AuthEnableRequest, AuthEnableResponse};
@@ -11,6 +12,9 @@ pub use auth_status::{AuthStatusRequest, AuthStatusResponse};
 pub use authenticate::{AuthenticateRequest, AuthenticateResponse};
 pub use role_add::{AuthRoleAddRequest, AuthRoleAddResponse};
 pub use role_delete::{AuthRoleDeleteRequest, AuthRoleDeleteResponse};
+pub use role_grant_permission::{
+    AuthRoleGrantPermissionRequest, AuthRoleGrantPermissionResponse, Permission,
+};
 pub use role_list::{AuthRoleListRequest, AuthRoleListResponse};
 
 use std::future::Future;
@@ -31,4 +35,10 @@ pub trait AuthOp {
     where
         R: Into<AuthRoleDeleteRequest>;
     fn role_list(&self) -> impl Future<Output = Result<AuthRoleListResponse>>;
+    fn role_grant_permission<R>(
+        &self,
+        req: R,
+    ) -> impl Future<Output = Result<AuthRoleGrantPermissionResponse>>
+    where
+        R: Into<AuthRoleGrantPermissionRequest>;
 }
@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct AuthRoleAddRequest {
@@ -34,10 +34,12 @@ pub struct AuthRoleAddResponse {
     pub header: ResponseHeader,
 }
 
-impl From<etcdserverpb::AuthRoleAddResponse> for AuthRoleAddResponse {
-    fn from(proto: etcdserverpb::AuthRoleAddResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
-        }
+impl TryFrom<etcdserverpb::AuthRoleAddResponse> for AuthRoleAddResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthRoleAddResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
     }
 }
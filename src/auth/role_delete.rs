@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct AuthRoleDeleteRequest {
@@ -34,10 +34,12 @@ pub struct AuthRoleDeleteResponse {
     pub header: ResponseHeader,
 }
 
-impl From<etcdserverpb::AuthRoleDeleteResponse> for AuthRoleDeleteResponse {
-    fn from(proto: etcdserverpb::AuthRoleDeleteResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
-        }
+impl TryFrom<etcdserverpb::AuthRoleDeleteResponse> for AuthRoleDeleteResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthRoleDeleteResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
     }
 }
@@ -0,0 +1,136 @@
+use crate::proto::{authpb, etcdserverpb};
+use crate::{Error, KeyRange, ResponseHeader, Result};
+
+use authpb::permission::Type;
+
+/// Permission grants a role access to a key or a range of keys.
+#[derive(Debug, Clone)]
+pub struct Permission {
+    proto: authpb::Permission,
+}
+
+impl Permission {
+    fn new(perm_type: Type, key_range: KeyRange) -> Self {
+        Self {
+            proto: authpb::Permission {
+                perm_type: perm_type as i32,
+                key: key_range.key,
+                range_end: key_range.range_end,
+            },
+        }
+    }
+
+    /// Creates a new Permission to read the keys of the range.
+    pub fn read(key_range: KeyRange) -> Self {
+        Self::new(Type::Read, key_range)
+    }
+
+    /// Creates a new Permission to write the keys of the range.
+    pub fn write(key_range: KeyRange) -> Self {
+        Self::new(Type::Write, key_range)
+    }
+
+    /// Creates a new Permission to read and write the keys of the range.
+    pub fn readwrite(key_range: KeyRange) -> Self {
+        Self::new(Type::Readwrite, key_range)
+    }
+
+    /// Creates a new Permission to read a single key.
+    pub fn read_key(key: impl Into<Vec<u8>>) -> Self {
+        Self::read(KeyRange::key(key))
+    }
+
+    /// Creates a new Permission to read the keys under the prefix, like
+    /// `etcdctl role grant-permission --prefix <role> read <prefix>`.
+    pub fn read_prefix(prefix: impl Into<Vec<u8>>) -> Self {
+        Self::read(KeyRange::prefix(prefix))
+    }
+
+    /// Creates a new Permission to write the keys under the prefix.
+    pub fn write_prefix(prefix: impl Into<Vec<u8>>) -> Self {
+        Self::write(KeyRange::prefix(prefix))
+    }
+
+    /// Creates a new Permission to read and write the keys under the prefix.
+    pub fn readwrite_prefix(prefix: impl Into<Vec<u8>>) -> Self {
+        Self::readwrite(KeyRange::prefix(prefix))
+    }
+
+    /// Returns the first key the permission applies to.
+    pub fn key(&self) -> &[u8] {
+        &self.proto.key
+    }
+
+    /// Returns the end of the range the permission applies to, which is empty for a single key.
+    pub fn range_end(&self) -> &[u8] {
+        &self.proto.range_end
+    }
+}
+
+impl From<Permission> for authpb::Permission {
+    fn from(perm: Permission) -> Self {
+        perm.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthRoleGrantPermissionRequest {
+    proto: etcdserverpb::AuthRoleGrantPermissionRequest,
+}
+
+impl AuthRoleGrantPermissionRequest {
+    /// Creates a new AuthRoleGrantPermissionRequest granting the permission to the role.
+    pub fn new(name: impl Into<String>, perm: Permission) -> Self {
+        Self {
+            proto: etcdserverpb::AuthRoleGrantPermissionRequest {
+                name: name.into(),
+                perm: Some(perm.into()),
+            },
+        }
+    }
+}
+
+impl<N> From<(N, Permission)> for AuthRoleGrantPermissionRequest
+where
+    N: Into<String>,
+{
+    fn from((name, perm): (N, Permission)) -> Self {
+        Self::new(name, perm)
+    }
+}
+
+impl From<AuthRoleGrantPermissionRequest> for etcdserverpb::AuthRoleGrantPermissionRequest {
+    fn from(req: AuthRoleGrantPermissionRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthRoleGrantPermissionResponse {
+    pub header: ResponseHeader,
+}
+
+impl TryFrom<etcdserverpb::AuthRoleGrantPermissionResponse> for AuthRoleGrantPermissionResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthRoleGrantPermissionResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_permission_range_matches_etcdctl() {
+        let perm = Permission::read_prefix("foo/");
+        assert_eq!(perm.key(), b"foo/");
+        assert_eq!(perm.range_end(), b"foo0");
+
+        let perm = Permission::readwrite_prefix(vec![0xff]);
+        assert_eq!(perm.range_end(), [0]);
+    }
+}
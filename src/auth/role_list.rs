@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Default, Clone)]
 pub struct AuthRoleListRequest {
@@ -18,11 +18,13 @@ pub struct AuthRoleListResponse {
     pub roles: Vec<String>,
 }
 
-impl From<etcdserverpb::AuthRoleListResponse> for AuthRoleListResponse {
-    fn from(proto: etcdserverpb::AuthRoleListResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::AuthRoleListResponse> for AuthRoleListResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::AuthRoleListResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             roles: proto.roles,
-        }
+        })
     }
 }
@@ -0,0 +1,112 @@
+//! A circuit breaker short-circuiting requests while etcd is unhealthy.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::{Error, Result};
+
+/// Thresholds of the circuit breaker of a client. See [`crate::ClientConfig::circuit_breaker`].
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitBreakerConfig {
+    /// How many consecutive failures open the circuit.
+    pub failure_threshold: u32,
+    /// How close together the failures must be to count as consecutive.
+    pub window: Duration,
+    /// How long the circuit stays open before a request is let through to test recovery.
+    pub cooldown: Duration,
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Closed {
+        failures: u32,
+        first_failure: Instant,
+    },
+    Open {
+        until: Instant,
+    },
+}
+
+pub(crate) struct CircuitBreaker {
+    cfg: CircuitBreakerConfig,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(cfg: CircuitBreakerConfig) -> Self {
+        Self {
+            cfg,
+            state: Mutex::new(State::Closed {
+                failures: 0,
+                first_failure: Instant::now(),
+            }),
+        }
+    }
+
+    /// Checks whether a request may be sent. Once the cooldown is over, a single request is let
+    /// through, and the circuit stays open for another cooldown unless it succeeds.
+    pub(crate) fn check(&self) -> Result<()> {
+        let mut state = self.lock()?;
+        if let State::Open { until } = &mut *state {
+            let now = Instant::now();
+            if now < *until {
+                return Err(Error::CircuitOpen);
+            }
+            *until = now + self.cfg.cooldown;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn record_success(&self) -> Result<()> {
+        *self.lock()? = State::Closed {
+            failures: 0,
+            first_failure: Instant::now(),
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn record_failure(&self) -> Result<()> {
+        let mut state = self.lock()?;
+        let now = Instant::now();
+
+        *state = match *state {
+            State::Closed {
+                failures,
+                first_failure,
+            } if failures > 0 && now.duration_since(first_failure) <= self.cfg.window => {
+                State::Closed {
+                    failures: failures + 1,
+                    first_failure,
+                }
+            }
+            State::Closed { .. } => State::Closed {
+                failures: 1,
+                first_failure: now,
+            },
+            // The request let through after the cooldown failed.
+            State::Open { .. } => State::Open {
+                until: now + self.cfg.cooldown,
+            },
+        };
+
+        if let State::Closed { failures, .. } = *state {
+            if failures >= self.cfg.failure_threshold {
+                *state = State::Open {
+                    until: now + self.cfg.cooldown,
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, State>> {
+        self.state
+            .lock()
+            .map_err(|e| Error::PoisonError(e.to_string()))
+    }
+}
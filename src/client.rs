@@ -1,44 +1,96 @@
-use std::{future::Future, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-use tokio::sync::{mpsc::channel, RwLock};
+use futures::{
+    future::{self, select, Either},
+    stream, Stream, StreamExt,
+};
+use tokio::{
+    sync::{
+        mpsc::{channel, unbounded_channel},
+        watch, RwLock,
+    },
+    time::Instant,
+};
 use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
 use tonic::{
-    metadata::{Ascii, MetadataValue},
+    codec::CompressionEncoding,
+    metadata::{Ascii, MetadataKey, MetadataValue},
     transport::Channel,
     Status,
 };
 
+use crate::error::{is_ambiguous_leader_change, is_leader_change};
 use crate::{
     auth::{AuthDisableRequest, AuthEnableRequest, AuthRoleListRequest},
     proto::etcdserverpb::LeaseKeepAliveRequest,
 };
 use crate::{
     auth::{AuthOp, AuthenticateResponse},
+    circuit_breaker::{CircuitBreaker, CircuitBreakerConfig},
     cluster::{
         ClusterOp, MemberAddRequest, MemberAddResponse, MemberListRequest, MemberListResponse,
         MemberRemoveRequest, MemberRemoveResponse, MemberUpdateRequest, MemberUpdateResponse,
     },
+    election::{
+        CampaignRequest, CampaignResponse, ElectionOp, LeaderKey, LeaderRequest, LeaderResponse,
+        ProclaimRequest, ProclaimResponse, ResignRequest, ResignResponse,
+    },
     kv::{
-        CompactRequest, CompactResponse, DeleteRequest, DeleteResponse, KeyRange, KeyValueOp,
-        PutRequest, PutResponse, RangeRequest, RangeResponse, TxnRequest, TxnResponse,
+        AutoCompactor, CompactRequest, CompactResponse, CompactRetention, DeleteCompaction,
+        DeleteRequest, DeleteResponse, Key, KeyRange, KeyValueOp, PutRequest, PutResponse,
+        RangeRequest, RangeResponse, SortOrder, TxnCmp, TxnCompare, TxnOp, TxnOpResponse,
+        TxnRequest, TxnResponse,
     },
     lease::{
-        LeaseGrantRequest, LeaseGrantResponse, LeaseId, LeaseKeepAlive, LeaseOp,
-        LeaseRevokeRequest, LeaseRevokeResponse, LeaseTimeToLiveRequest, LeaseTimeToLiveResponse,
+        AutoKeepAlive, LeaseGrantRequest, LeaseGrantResponse, LeaseId, LeaseKeepAlive,
+        LeaseLeasesRequest, LeaseLeasesResponse, LeaseOp, LeaseRegranted, LeaseRevokeRequest,
+        LeaseRevokeResponse, LeaseStats, LeaseTimeToLiveRequest, LeaseTimeToLiveResponse,
+        MultiKeepAlive,
+    },
+    lock::{LockOp, LockRequest, LockResponse, UnlockRequest, UnlockResponse},
+    maintenance::{
+        ConsistencyReport, DowngradeRequest, DowngradeResponse, Feature, HashKvRequest,
+        HashKvResponse, MaintenanceOp, StatusRequest, StatusResponse,
     },
     proto::etcdserverpb,
     proto::etcdserverpb::cluster_client::ClusterClient,
     proto::etcdserverpb::{
         auth_client::AuthClient, kv_client::KvClient, lease_client::LeaseClient,
-        watch_client::WatchClient,
+        maintenance_client::MaintenanceClient, watch_client::WatchClient,
+    },
+    proto::v3electionpb::election_client::ElectionClient,
+    proto::v3lockpb::lock_client::LockClient,
+    session::{Session, SessionState},
+    watch::{
+        progress_request, Event, EventBatch, EventType, KeyTransition, MembershipChanges,
+        ReconcilingWatch, ResumableWatch, WatchCanceler, WatchCreateRequest, WatchEvents,
+        WatchInbound, WatchOp, WatchStream,
     },
-    watch::{WatchCanceler, WatchCreateRequest, WatchOp, WatchStream},
     AuthDisableResponse, AuthEnableResponse, AuthRoleAddRequest, AuthRoleAddResponse,
-    AuthRoleDeleteRequest, AuthRoleDeleteResponse, AuthRoleListResponse, AuthStatusRequest,
-    AuthStatusResponse, AuthenticateRequest, Error, Result,
+    AuthRoleDeleteRequest, AuthRoleDeleteResponse, AuthRoleGrantPermissionRequest,
+    AuthRoleGrantPermissionResponse, AuthRoleListResponse, AuthStatusRequest, AuthStatusResponse,
+    AuthenticateRequest, Error, KeyValue, Member, Result,
 };
 
-static MAX_RETRY: i32 = 3;
+static DEFAULT_MAX_RETRIES: u32 = 2;
+/// etcd's default `--max-request-bytes`.
+static DEFAULT_MAX_REQUEST_BYTES: usize = 1536 * 1024;
+static DEFAULT_WATCH_BUFFER: usize = 128;
+static REGRANT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+static CAS_MAX_ATTEMPTS: u32 = 16;
+
+static GET_AFTER_RETRY_INTERVAL: Duration = Duration::from_millis(50);
 
 #[derive(Debug, Clone)]
 pub struct Endpoint {
@@ -56,6 +108,28 @@ impl Endpoint {
         }
     }
 
+    /// Whether the endpoint is connected to with TLS, which is the case of `https://` URLs even
+    /// without a TLS config, in which case the root certificates of the platform are trusted.
+    pub fn is_tls(&self) -> bool {
+        #[cfg(feature = "tls")]
+        return self.tls_opt.is_some() || self.is_https();
+
+        #[cfg(not(feature = "tls"))]
+        false
+    }
+
+    #[cfg(feature = "tls")]
+    fn is_https(&self) -> bool {
+        self.url.starts_with("https://")
+    }
+
+    /// Returns a copy of the endpoint pointing to another URL.
+    fn with_url(&self, url: impl Into<String>) -> Self {
+        let mut endpoint = self.clone();
+        endpoint.url = url.into();
+        endpoint
+    }
+
     #[cfg(feature = "tls")]
     pub fn tls_raw(
         mut self,
@@ -95,6 +169,25 @@ impl Endpoint {
 
         Ok(self.tls_raw(domain_name, ca_cert, client_cert, client_key))
     }
+
+    /// Connects with TLS which only authenticates the server, for servers which do not require
+    /// client certificates, e.g. when clients authenticate with a token instead.
+    #[cfg(feature = "tls")]
+    pub fn tls_server_auth(
+        mut self,
+        domain_name: impl Into<String>,
+        ca_cert: impl AsRef<[u8]>,
+    ) -> Self {
+        use tonic::transport::{Certificate, ClientTlsConfig};
+
+        self.tls_opt = Some(
+            ClientTlsConfig::new()
+                .domain_name(domain_name)
+                .ca_certificate(Certificate::from_pem(ca_cert)),
+        );
+
+        self
+    }
 }
 
 impl<T> From<T> for Endpoint
@@ -110,13 +203,74 @@ where
     }
 }
 
+/// Which member serializable range requests are sent to.
+///
+/// Linearizable reads and writes always go through the balanced channel, since they are served by
+/// the leader anyway.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadPreference {
+    /// Sends serializable reads to the member that is the leader when the client connects.
+    Leader,
+    /// Balances serializable reads over all configured endpoints.
+    #[default]
+    AnyMember,
+    /// Sends serializable reads to the first configured endpoint, which should be the nearest one.
+    Nearest,
+}
+
+/// How requests are spread over the endpoints of the balanced channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LoadBalancing {
+    /// Spreads requests over all configured endpoints. tonic picks among the ready endpoints at
+    /// random, since its connections do not report their load.
+    #[default]
+    AllMembers,
+    /// Spreads requests over the given number of endpoints which answered a status request the
+    /// fastest when connecting, to keep slow members off the request path. Unreachable endpoints
+    /// rank last, so they are only used if not enough endpoints are reachable.
+    Fastest(usize),
+}
+
+/// Callback invoked before each retry of a request. See [`ClientConfig::on_retry`].
+#[derive(Clone)]
+struct RetryHook(Arc<dyn Fn(u32, tonic::Code) + Send + Sync>);
+
+impl fmt::Debug for RetryHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RetryHook")
+    }
+}
+
 /// Config for establishing etcd client.
 #[derive(Clone, Debug)]
 pub struct ClientConfig {
     pub endpoints: Vec<Endpoint>,
     pub auth: Option<(String, String)>,
+    pub token: Option<String>,
     pub connect_timeout: Duration,
     pub http2_keep_alive_interval: Duration,
+    pub http2_keep_alive_timeout: Duration,
+    pub http2_keep_alive_while_idle: bool,
+    pub tcp_nodelay: bool,
+    pub tcp_keepalive: Option<Duration>,
+    pub max_retries: u32,
+    pub compression: Option<CompressionEncoding>,
+    pub read_preference: ReadPreference,
+    pub load_balancing: LoadBalancing,
+    pub max_request_bytes: usize,
+    pub require_tls: bool,
+    pub startup_attempts: u32,
+    pub startup_backoff: Duration,
+    pub request_timeout: Option<Duration>,
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    pub pin_leader_for_writes: bool,
+    pub watch_buffer: usize,
+    pub auth_header_name: MetadataKey<Ascii>,
+    pub compact_after_deletes: Option<DeleteCompaction>,
+    on_retry: Option<RetryHook>,
+    #[cfg(feature = "srv")]
+    pub discover_srv: Option<(String, String)>,
 }
 
 impl ClientConfig {
@@ -124,8 +278,30 @@ impl ClientConfig {
         Self {
             endpoints: endpoints.into(),
             auth: None,
+            token: None,
             connect_timeout: Duration::from_secs(30),
             http2_keep_alive_interval: Duration::from_secs(5),
+            http2_keep_alive_timeout: Duration::from_secs(20),
+            http2_keep_alive_while_idle: false,
+            tcp_nodelay: true,
+            tcp_keepalive: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            compression: None,
+            read_preference: ReadPreference::default(),
+            load_balancing: LoadBalancing::default(),
+            max_request_bytes: DEFAULT_MAX_REQUEST_BYTES,
+            require_tls: false,
+            startup_attempts: 1,
+            startup_backoff: Duration::ZERO,
+            request_timeout: None,
+            circuit_breaker: None,
+            pin_leader_for_writes: false,
+            watch_buffer: DEFAULT_WATCH_BUFFER,
+            auth_header_name: MetadataKey::from_static("authorization"),
+            compact_after_deletes: None,
+            on_retry: None,
+            #[cfg(feature = "srv")]
+            discover_srv: None,
         }
     }
 
@@ -134,6 +310,25 @@ impl ClientConfig {
         self
     }
 
+    /// Sets the metadata key the authentication token is sent under, `authorization` by default,
+    /// e.g. `x-etcd-token` for a gateway in front of etcd which expects another header.
+    ///
+    /// # Errors
+    /// Will return `Err` if the name is not a valid ASCII metadata key.
+    pub fn auth_header_name(mut self, name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        self.auth_header_name =
+            MetadataKey::from_bytes(name.as_bytes()).map_err(|_| Error::InvalidHeaderName(name))?;
+        Ok(self)
+    }
+
+    /// Sets an authentication token obtained out-of-band, so that no authentication is made on
+    /// connect. If credentials are set as well, they are used to refresh the token once it expires.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
     pub fn connect_timeout(mut self, timeout: Duration) -> Self {
         self.connect_timeout = timeout;
         self
@@ -143,6 +338,196 @@ impl ClientConfig {
         self.http2_keep_alive_interval = interval;
         self
     }
+
+    /// Sets how long to wait for a keepalive ping to be acknowledged before closing the connection.
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.http2_keep_alive_timeout = timeout;
+        self
+    }
+
+    /// Sets whether keepalive pings are sent while the connection has no in-flight requests, which
+    /// keeps idle connections from being dropped by proxies and load balancers.
+    pub fn http2_keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.http2_keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// Sets whether Nagle's algorithm is disabled on the TCP connections. Enabled by default.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Sets the OS-level TCP keepalive interval of the connections, or disables it with `None`.
+    pub fn tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.tcp_keepalive = interval;
+        self
+    }
+
+    /// Sets how many times a failed request is retried. With 0 a request is attempted exactly once.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Disables automatic retries, so the first error of a request is returned immediately.
+    pub fn no_retry(self) -> Self {
+        self.max_retries(0)
+    }
+
+    /// Compresses requests with the given encoding and accepts responses compressed with it.
+    /// Encodings are enabled by the crate features of the same name, e.g. `gzip`.
+    pub fn compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = Some(encoding);
+        self
+    }
+
+    /// Sets which member serializable range requests are routed to.
+    pub fn read_preference(mut self, preference: ReadPreference) -> Self {
+        self.read_preference = preference;
+        self
+    }
+
+    /// Sets how requests are spread over the endpoints. See [`LoadBalancing`].
+    pub fn load_balancing(mut self, policy: LoadBalancing) -> Self {
+        self.load_balancing = policy;
+        self
+    }
+
+    /// Sets the size above which puts and transactions are rejected before being sent, which
+    /// should match the `--max-request-bytes` of the cluster. Defaults to etcd's 1.5 MiB.
+    pub fn max_request_bytes(mut self, limit: usize) -> Self {
+        self.max_request_bytes = limit;
+        self
+    }
+
+    /// Sets how many requests, i.e. watch creations and cancellations, can be queued on a watch
+    /// stream before sending more waits, 128 by default.
+    ///
+    /// Responses are not buffered by the client beyond the HTTP/2 flow control window: a consumer
+    /// which does not poll its watch stream stops the server from sending more. etcd then buffers
+    /// a limited number of responses per stream, after which the watch falls behind and its events
+    /// are read back from the store once the consumer catches up, so a slow consumer costs
+    /// neither side unbounded memory, but it may miss revisions compacted in the meantime.
+    pub fn watch_buffer(mut self, capacity: usize) -> Self {
+        self.watch_buffer = capacity.max(1);
+        self
+    }
+
+    /// Refuses to connect if credentials or a token are set while an endpoint is plaintext, so
+    /// that they are never sent unencrypted.
+    pub fn require_tls(mut self) -> Self {
+        self.require_tls = true;
+        self
+    }
+
+    /// Makes `Client::new` try to connect and authenticate up to `attempts` times, waiting
+    /// `backoff` between attempts, e.g. while etcd is still starting. Defaults to a single attempt.
+    /// Invalid configs and rejected credentials are not retried.
+    pub fn startup_retry(mut self, attempts: u32, backoff: Duration) -> Self {
+        self.startup_attempts = attempts;
+        self.startup_backoff = backoff;
+        self
+    }
+
+    /// Sets the deadline of unary requests, which is sent to the server as `grpc-timeout` so that
+    /// it abandons the request once the client has given up on it. Each retry gets a new deadline.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Compacts the key-value store, keeping the `keep_revisions` most recent revisions, each time
+    /// `deleted_keys` keys have been deleted through the client since its last compaction, so
+    /// that churny workloads like queues do not accumulate deleted revisions.
+    ///
+    /// The compaction runs in the background, and a failed one is retried after the next delete.
+    /// Keys deleted in transactions or by other clients are not counted.
+    pub fn compact_after_deletes(mut self, deleted_keys: u64, keep_revisions: i64) -> Self {
+        self.compact_after_deletes = Some(DeleteCompaction {
+            deleted_keys,
+            keep_revisions,
+        });
+        self
+    }
+
+    /// Fails requests with `Error::CircuitOpen` without sending them for `cooldown` after
+    /// `failure_threshold` consecutive failures, each within `window` of the first one. Requests
+    /// fail when etcd is unavailable or misses their deadline, not when they are refused.
+    pub fn circuit_breaker(
+        mut self,
+        failure_threshold: u32,
+        window: Duration,
+        cooldown: Duration,
+    ) -> Self {
+        self.circuit_breaker = Some(CircuitBreakerConfig {
+            failure_threshold,
+            window,
+            cooldown,
+        });
+        self
+    }
+
+    /// Sends the writes of the KV API straight to the leader instead of letting a follower forward
+    /// them, which saves a hop and keeps writes working when that follower is partitioned from the
+    /// leader. The leader is looked up again whenever a write reports it is lost or changed, and
+    /// the write is retried on the new one within the `max_retries` budget.
+    pub fn pin_leader_for_writes(mut self) -> Self {
+        self.pin_leader_for_writes = true;
+        self
+    }
+
+    /// Calls `hook` before each retry of a request, with the number of the retry starting at 1
+    /// and the code of the failure which caused it, e.g. to log or count retries.
+    pub fn on_retry<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(u32, tonic::Code) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(RetryHook(Arc::new(hook)));
+        self
+    }
+
+    /// Connects to all the configured endpoints with the TLS config, replacing their own, and
+    /// upgrading their `http://` URLs to `https://`, so that no endpoint is left in plaintext.
+    #[cfg(feature = "tls")]
+    pub fn with_tls_all(mut self, tls: tonic::transport::ClientTlsConfig) -> Self {
+        for e in self.endpoints.iter_mut() {
+            if let Some(rest) = e.url.strip_prefix("http://") {
+                e.url = format!("https://{rest}");
+            }
+            e.tls_opt = Some(tls.clone());
+        }
+        self
+    }
+
+    /// Discovers the members from the `_<service>._tcp.<domain>` SRV records when connecting,
+    /// e.g. `discover_srv("etcd-client", "example.com")`. Services ending with `-ssl` use https.
+    ///
+    /// Discovered endpoints are added to the configured ones, and use the TLS config of the first
+    /// configured endpoint, if any.
+    #[cfg(feature = "srv")]
+    pub fn discover_srv(mut self, service: impl Into<String>, domain: impl Into<String>) -> Self {
+        self.discover_srv = Some((service.into(), domain.into()));
+        self
+    }
+
+    /// Resolves the SRV records of the config into endpoints.
+    #[cfg(feature = "srv")]
+    async fn resolve_srv(mut self) -> Result<Self> {
+        if let Some((service, domain)) = self.discover_srv.take() {
+            let urls = crate::discovery::lookup_srv(&service, &domain).await?;
+            let endpoints: Vec<Endpoint> = urls
+                .into_iter()
+                .map(|url| match self.endpoints.first() {
+                    Some(e) => e.with_url(url),
+                    None => Endpoint::new(url),
+                })
+                .collect();
+            self.endpoints.extend(endpoints);
+        }
+
+        Ok(self)
+    }
 }
 
 /// Client is an abstraction for grouping etcd operations and managing underlying network communications.
@@ -150,11 +535,20 @@ impl ClientConfig {
 pub struct Client {
     auth_client: AuthClient<Channel>,
     kv_client: KvClient<Channel>,
+    read_kv_client: Option<KvClient<Channel>>,
+    write_kv_client: Arc<RwLock<Option<KvClient<Channel>>>>,
     watch_client: WatchClient<Channel>,
     cluster_client: ClusterClient<Channel>,
     lease_client: LeaseClient<Channel>,
+    maintenance_client: MaintenanceClient<Channel>,
+    election_client: ElectionClient<Channel>,
+    lock_client: LockClient<Channel>,
     token: Arc<RwLock<Option<MetadataValue<Ascii>>>>,
     auth_user: Option<(String, String)>,
+    max_retries: u32,
+    breaker: Option<Arc<CircuitBreaker>>,
+    deletes_since_compaction: Arc<AtomicU64>,
+    cfg: ClientConfig,
 }
 
 impl AuthOp for Client {
@@ -165,52 +559,49 @@ impl AuthOp for Client {
         let req = tonic::Request::new(req.into().into());
         let resp = self.auth_client.clone().authenticate(req).await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn auth_status(&self) -> Result<AuthStatusResponse> {
         let req = tonic::Request::new(AuthStatusRequest::default().into());
-        let resp = match self.auth_user {
-            Some(_) => {
-                self.execute_with_retries(req, |req| async {
-                    self.auth_client.clone().auth_status(req).await
-                })
-                .await?
-            }
-            None => self.auth_client.clone().auth_status(req).await?,
+        let resp = if self.has_credentials() {
+            self.execute_with_retries(req, |req| async {
+                self.auth_client.clone().auth_status(req).await
+            })
+            .await?
+        } else {
+            self.auth_client.clone().auth_status(req).await?
         };
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn auth_enable(&self) -> Result<AuthEnableResponse> {
         let req = tonic::Request::new(AuthEnableRequest::default().into());
-        let resp = match self.auth_user {
-            Some(_) => {
-                self.execute_with_retries(req, |req| async {
-                    self.auth_client.clone().auth_enable(req).await
-                })
-                .await?
-            }
-            None => self.auth_client.clone().auth_enable(req).await?,
+        let resp = if self.has_credentials() {
+            self.execute_with_retries(req, |req| async {
+                self.auth_client.clone().auth_enable(req).await
+            })
+            .await?
+        } else {
+            self.auth_client.clone().auth_enable(req).await?
         };
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn auth_disable(&self) -> Result<AuthDisableResponse> {
         let req = tonic::Request::new(AuthDisableRequest::default().into());
-        let resp = match self.auth_user {
-            Some(_) => {
-                self.execute_with_retries(req, |req| async {
-                    self.auth_client.clone().auth_disable(req).await
-                })
-                .await?
-            }
-            None => self.auth_client.clone().auth_disable(req).await?,
+        let resp = if self.has_credentials() {
+            self.execute_with_retries(req, |req| async {
+                self.auth_client.clone().auth_disable(req).await
+            })
+            .await?
+        } else {
+            self.auth_client.clone().auth_disable(req).await?
         };
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn role_add<R>(&self, req: R) -> Result<AuthRoleAddResponse>
@@ -218,17 +609,16 @@ impl AuthOp for Client {
         R: Into<AuthRoleAddRequest>,
     {
         let req = tonic::Request::new(req.into().into());
-        let resp = match self.auth_user {
-            Some(_) => {
-                self.execute_with_retries(req, |req| async {
-                    self.auth_client.clone().role_add(req).await
-                })
-                .await?
-            }
-            None => self.auth_client.clone().role_add(req).await?,
+        let resp = if self.has_credentials() {
+            self.execute_with_retries(req, |req| async {
+                self.auth_client.clone().role_add(req).await
+            })
+            .await?
+        } else {
+            self.auth_client.clone().role_add(req).await?
         };
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn role_delete<R>(&self, req: R) -> Result<AuthRoleDeleteResponse>
@@ -236,32 +626,47 @@ impl AuthOp for Client {
         R: Into<AuthRoleDeleteRequest>,
     {
         let req = tonic::Request::new(req.into().into());
-        let resp = match self.auth_user {
-            Some(_) => {
-                self.execute_with_retries(req, |req| async {
-                    self.auth_client.clone().role_delete(req).await
-                })
-                .await?
-            }
-            None => self.auth_client.clone().role_delete(req).await?,
+        let resp = if self.has_credentials() {
+            self.execute_with_retries(req, |req| async {
+                self.auth_client.clone().role_delete(req).await
+            })
+            .await?
+        } else {
+            self.auth_client.clone().role_delete(req).await?
         };
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn role_list(&self) -> Result<AuthRoleListResponse> {
         let req = tonic::Request::new(AuthRoleListRequest::default().into());
-        let resp = match self.auth_user {
-            Some(_) => {
-                self.execute_with_retries(req, |req| async {
-                    self.auth_client.clone().role_list(req).await
-                })
-                .await?
-            }
-            None => self.auth_client.clone().role_list(req).await?,
+        let resp = if self.has_credentials() {
+            self.execute_with_retries(req, |req| async {
+                self.auth_client.clone().role_list(req).await
+            })
+            .await?
+        } else {
+            self.auth_client.clone().role_list(req).await?
+        };
+
+        resp.into_inner().try_into()
+    }
+
+    async fn role_grant_permission<R>(&self, req: R) -> Result<AuthRoleGrantPermissionResponse>
+    where
+        R: Into<AuthRoleGrantPermissionRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = if self.has_credentials() {
+            self.execute_with_retries(req, |req| async {
+                self.auth_client.clone().role_grant_permission(req).await
+            })
+            .await?
+        } else {
+            self.auth_client.clone().role_grant_permission(req).await?
         };
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 }
 
@@ -272,15 +677,32 @@ impl Client {
             #[cfg(not(feature = "tls"))]
             let c = Channel::from_shared(e.url.clone())?
                 .connect_timeout(cfg.connect_timeout)
-                .http2_keep_alive_interval(cfg.http2_keep_alive_interval);
+                .http2_keep_alive_interval(cfg.http2_keep_alive_interval)
+                .keep_alive_timeout(cfg.http2_keep_alive_timeout)
+                .keep_alive_while_idle(cfg.http2_keep_alive_while_idle)
+                .tcp_nodelay(cfg.tcp_nodelay)
+                .tcp_keepalive(cfg.tcp_keepalive);
 
             #[cfg(feature = "tls")]
             let mut c = Channel::from_shared(e.url.clone())?
                 .connect_timeout(cfg.connect_timeout)
-                .http2_keep_alive_interval(cfg.http2_keep_alive_interval);
+                .http2_keep_alive_interval(cfg.http2_keep_alive_interval)
+                .keep_alive_timeout(cfg.http2_keep_alive_timeout)
+                .keep_alive_while_idle(cfg.http2_keep_alive_while_idle)
+                .tcp_nodelay(cfg.tcp_nodelay)
+                .tcp_keepalive(cfg.tcp_keepalive);
             #[cfg(feature = "tls")]
             {
-                if let Some(tls) = e.tls_opt.to_owned() {
+                // `https://` endpoints without a TLS config trust the native roots of the
+                // platform, which the `tls` feature enables in tonic.
+                let tls = match e.tls_opt.to_owned() {
+                    Some(tls) => Some(tls),
+                    None if e.is_https() => {
+                        Some(tonic::transport::ClientTlsConfig::new().with_enabled_roots())
+                    }
+                    None => None,
+                };
+                if let Some(tls) = tls {
                     c = c.tls_config(tls)?;
                 }
             }
@@ -288,131 +710,1993 @@ impl Client {
             endpoints.push(c);
         }
 
+        if let LoadBalancing::Fastest(n) = cfg.load_balancing {
+            if n < endpoints.len() {
+                endpoints = Self::fastest_endpoints(endpoints, n.max(1), cfg.connect_timeout).await;
+            }
+        }
+
         Ok(Channel::balance_list(endpoints.into_iter()))
     }
 
+    /// Returns the `n` endpoints answering a status request the fastest, waiting for each one at
+    /// most `timeout`.
+    async fn fastest_endpoints(
+        endpoints: Vec<tonic::transport::Endpoint>,
+        n: usize,
+        timeout: Duration,
+    ) -> Vec<tonic::transport::Endpoint> {
+        let probes = endpoints.into_iter().map(|e| async move {
+            let started = Instant::now();
+            let probe = async {
+                let channel = e.connect().await.ok()?;
+                MaintenanceClient::new(channel)
+                    .status(etcdserverpb::StatusRequest::default())
+                    .await
+                    .ok()
+            };
+            let reached = matches!(tokio::time::timeout(timeout, probe).await, Ok(Some(_)));
+            // Unreachable endpoints rank after all reachable ones.
+            (!reached, started.elapsed(), e)
+        });
+
+        let mut ranked = future::join_all(probes).await;
+        ranked.sort_by_key(|(unreachable, elapsed, _)| (*unreachable, *elapsed));
+        ranked.into_iter().take(n).map(|(_, _, e)| e).collect()
+    }
+
     /// new connect to etcd cluster and returns a client.
     ///
+    /// If credentials are set but authentication is not enabled on the cluster yet, the client
+    /// connects without a token, and authenticates once the cluster requires it.
+    ///
     /// # Errors
-    /// Will returns `Err` if failed to contact with given endpoints or authentication failed.
+    /// Will returns `Err` if failed to contact with given endpoints or authentication failed,
+    /// after as many attempts as configured with `ClientConfig::startup_retry`. Only failures to
+    /// reach the cluster are retried: invalid configs and rejected credentials fail at once.
     pub async fn new(cfg: ClientConfig) -> Result<Self> {
+        let mut attempt = 1;
+        loop {
+            match Self::establish(cfg.clone()).await {
+                Ok(cli) => return Ok(cli),
+                Err(e) if attempt >= cfg.startup_attempts || !e.is_transient() => return Err(e),
+                Err(_) => {
+                    attempt += 1;
+                    tokio::time::sleep(cfg.startup_backoff).await;
+                }
+            }
+        }
+    }
+
+    async fn establish(cfg: ClientConfig) -> Result<Self> {
+        #[cfg(feature = "srv")]
+        let cfg = cfg.resolve_srv().await?;
+
+        if cfg.require_tls && (cfg.auth.is_some() || cfg.token.is_some()) {
+            if let Some(e) = cfg.endpoints.iter().find(|e| !e.is_tls()) {
+                return Err(Error::TlsRequired(e.url.clone()));
+            }
+        }
+
+        let mut cli = Self::connect(cfg).await?;
+        cli.auth_user = cli.cfg.auth.clone();
+
+        match cli.cfg.token.clone() {
+            Some(token) => cli.set_metadata_token(&token).await?,
+            None => cli.refresh_token().await?,
+        }
+
+        cli.read_kv_client = cli.pin_reads().await?;
+        if cli.cfg.pin_leader_for_writes {
+            *cli.write_kv_client.write().await = Some(cli.leader_kv_client().await?);
+        }
+
+        Ok(cli)
+    }
+
+    async fn connect(cfg: ClientConfig) -> Result<Self> {
         let channel = Self::new_channel(&cfg).await?;
 
-        let auth_client = AuthClient::new(channel.clone());
-        let kv_client = KvClient::new(channel.clone());
-        let watch_client = WatchClient::new(channel.clone());
-        let cluster_client = ClusterClient::new(channel.clone());
-        let lease_client = LeaseClient::new(channel);
+        let mut auth_client = AuthClient::new(channel.clone());
+        let mut kv_client = KvClient::new(channel.clone());
+        let mut watch_client = WatchClient::new(channel.clone());
+        let mut cluster_client = ClusterClient::new(channel.clone());
+        let mut lease_client = LeaseClient::new(channel.clone());
+        let mut maintenance_client = MaintenanceClient::new(channel.clone());
+        let mut election_client = ElectionClient::new(channel.clone());
+        let mut lock_client = LockClient::new(channel);
+
+        if let Some(encoding) = cfg.compression {
+            auth_client = auth_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+            kv_client = kv_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+            watch_client = watch_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+            cluster_client = cluster_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+            lease_client = lease_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+            maintenance_client = maintenance_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+            election_client = election_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+            lock_client = lock_client
+                .send_compressed(encoding)
+                .accept_compressed(encoding);
+        }
 
-        let mut cli = Self {
+        Ok(Self {
             auth_client,
             kv_client,
+            read_kv_client: None,
             watch_client,
             cluster_client,
             lease_client,
+            maintenance_client,
+            election_client,
+            lock_client,
             auth_user: None,
             token: Arc::new(RwLock::new(None)),
-        };
-
-        if let Some((username, password)) = cfg.auth {
-            cli.auth_user = Some((username, password));
-            cli.refresh_token().await.unwrap();
-        };
-
-        Ok(cli)
+            write_kv_client: Arc::new(RwLock::new(None)),
+            max_retries: cfg.max_retries,
+            breaker: cfg
+                .circuit_breaker
+                .map(|breaker| Arc::new(CircuitBreaker::new(breaker))),
+            deletes_since_compaction: Arc::new(AtomicU64::new(0)),
+            cfg,
+        })
     }
 
-    async fn refresh_token(&self) -> Result<()> {
-        if let Some((username, password)) = &self.auth_user {
-            let token = self.authenticate((username, password)).await?.token;
-            let t = match MetadataValue::try_from(&token) {
-                Ok(t) => t,
-                Err(err) => return Err(Error::ParseMetadataToken(err.to_string())),
-            };
-            let mut x = self.token.write().await;
-            *x = Some(t);
-        }
+    /// Returns the etcd version of the member serving the requests, e.g. `3.5.9`.
+    pub async fn server_version(&self) -> Result<String> {
+        Ok(self.status().await?.version)
+    }
 
-        Ok(())
+    /// Whether the member serving the requests supports the feature.
+    pub async fn supports(&self, feature: Feature) -> Result<bool> {
+        Ok(feature.is_supported_by(&self.server_version().await?))
     }
 
-    async fn set_token<T>(&self, req: &mut tonic::Request<T>) {
-        let token = self.token.clone();
-        let h = token.read().await;
-        if let Some(token) = h.to_owned() {
-            req.metadata_mut().insert("authorization", token);
+    /// Returns `Err(Error::Unsupported)` unless the member serving the requests supports the
+    /// feature, to fail early before relying on it.
+    pub async fn require(&self, feature: Feature) -> Result<()> {
+        let version = self.server_version().await?;
+        if feature.is_supported_by(&version) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported { feature, version })
         }
     }
 
-    async fn execute_with_retries<F, Fut, T, R>(&self, req: tonic::Request<T>, f: F) -> Result<R>
-    where
-        F: Fn(tonic::Request<T>) -> Fut,
-        Fut: Future<Output = std::result::Result<R, Status>>,
-        T: Clone,
-    {
-        for _i in 1..=MAX_RETRY {
-            let mut new_req = tonic::Request::new(req.get_ref().clone());
-            self.set_token(&mut new_req).await;
+    /// Turns the failure of a request which the server does not implement into
+    /// `Error::Unsupported`.
+    async fn gate<T>(&self, resp: Result<T>, feature: Feature) -> Result<T> {
+        match resp {
+            Err(e)
+                if e.status().map(|status| status.code()) == Some(tonic::Code::Unimplemented) =>
+            {
+                let version = self
+                    .server_version()
+                    .await
+                    .unwrap_or_else(|_| "unknown".to_owned());
 
-            match f(new_req).await {
-                Ok(response) => {
-                    return Ok(response);
-                }
-                Err(status) => {
-                    if status.code() == tonic::Code::Unauthenticated {
-                        self.refresh_token().await?;
-                    } else if status.code() == tonic::Code::Unavailable {
-                        continue;
-                    } else {
-                        return Err(Error::Response(status));
-                    }
-                }
+                Err(Error::Unsupported { feature, version })
             }
+            resp => resp,
         }
-        Err(Error::ExecuteFailed)
     }
-}
-
-impl KeyValueOp for Client {
-    async fn put<R>(&self, req: R) -> Result<PutResponse>
-    where
-        R: Into<PutRequest>,
-    {
-        let req = tonic::Request::new(req.into().into());
-        let resp = self
-            .execute_with_retries(req, |req| async { self.kv_client.clone().put(req).await })
-            .await?;
 
-        Ok(resp.into_inner().into())
+    /// Checks whether the cluster can be downgraded to the version, e.g. `3.4`.
+    pub async fn downgrade_validate(
+        &self,
+        version: impl Into<String>,
+    ) -> Result<DowngradeResponse> {
+        self.downgrade(DowngradeRequest::validate(version)).await
     }
 
-    async fn get<R>(&self, req: R) -> Result<RangeResponse>
-    where
-        R: Into<RangeRequest>,
-    {
-        let req = tonic::Request::new(req.into().into());
-        let resp = self
-            .execute_with_retries(req, |req| async { self.kv_client.clone().range(req).await })
-            .await?;
+    /// Starts downgrading the cluster to the version, after which the members can be replaced by
+    /// binaries of that version one at a time.
+    pub async fn downgrade_enable(&self, version: impl Into<String>) -> Result<DowngradeResponse> {
+        self.downgrade(DowngradeRequest::enable(version)).await
+    }
 
-        Ok(resp.into_inner().into())
+    /// Cancels the ongoing downgrade of the cluster.
+    pub async fn downgrade_cancel(&self) -> Result<DowngradeResponse> {
+        self.downgrade(DowngradeRequest::cancel()).await
     }
 
-    async fn get_all(&self) -> Result<RangeResponse> {
-        self.get(KeyRange::all()).await
+    /// Hashes the keyspace of every member at the revision and reports the members whose hash
+    /// differs from the one most members agree on, to detect data corruption.
+    ///
+    /// A revision of 0 uses the current revision, so that all the members hash the same keys.
+    /// Members which have not started yet, and so have no client URL, are skipped.
+    pub async fn verify_consistency(&self, revision: i64) -> Result<ConsistencyReport> {
+        let revision = match revision {
+            0 => self.current_revision().await?,
+            revision => revision,
+        };
+
+        let mut hashes = HashMap::new();
+        for member in self.member_list().await?.members {
+            let Some(url) = member.client_urls.into_iter().next() else {
+                continue;
+            };
+
+            let resp = self.for_endpoint(url).await?.hash_kv(revision).await?;
+            hashes.insert(member.id, resp.hash);
+        }
+
+        Ok(ConsistencyReport::new(revision, hashes))
     }
 
-    async fn get_by_prefix<K>(&self, p: K) -> Result<RangeResponse>
-    where
-        K: Into<Vec<u8>>,
-    {
-        self.get(KeyRange::prefix(p)).await
+    /// Returns a client sharing the connections of this one, whose unary requests have the given
+    /// deadline. See [`ClientConfig::request_timeout`].
+    pub fn with_request_timeout(&self, timeout: Duration) -> Self {
+        let mut cli = self.clone();
+        cli.cfg.request_timeout = Some(timeout);
+        cli
     }
 
-    async fn get_range<F, E>(&self, from: F, end: E) -> Result<RangeResponse>
-    where
-        F: Into<Vec<u8>>,
-        E: Into<Vec<u8>>,
+    /// Establishes the connections ahead of the first request, so that it does not pay for them,
+    /// and returns whether each configured endpoint could be reached, by URL.
+    ///
+    /// A status request is sent through the shared channel, which connects it to the endpoints,
+    /// and to each endpoint on its own to tell which ones are reachable.
+    ///
+    /// # Errors
+    /// Will return `Err` if no endpoint can be reached through the shared channel.
+    pub async fn warm_up(&self) -> Result<Vec<(String, Result<()>)>> {
+        self.status().await?;
+
+        let checks = self.cfg.endpoints.iter().map(|e| async move {
+            let reached = match self.for_endpoint(e.url.clone()).await {
+                Ok(cli) => cli.status().await.map(|_| ()),
+                Err(err) => Err(err),
+            };
+            (e.url.clone(), reached)
+        });
+
+        Ok(future::join_all(checks).await)
+    }
+
+    /// Returns a client whose requests are all sent to the given endpoint.
+    ///
+    /// The endpoint reuses the TLS config of the matching configured endpoint, or of the first
+    /// configured one if the URL is not part of the config. The returned client shares the
+    /// authentication token with this one.
+    ///
+    /// # Errors
+    /// Will returns `Err` if the URL is invalid.
+    pub async fn for_endpoint(&self, url: impl Into<String>) -> Result<Self> {
+        let url = url.into();
+
+        let endpoint = match self.cfg.endpoints.iter().find(|e| e.url == url) {
+            Some(e) => e.clone(),
+            None => match self.cfg.endpoints.first() {
+                Some(e) => e.with_url(url),
+                None => Endpoint::new(url),
+            },
+        };
+
+        let mut cfg = self.cfg.clone();
+        cfg.endpoints = vec![endpoint];
+
+        let mut cli = Self::connect(cfg).await?;
+        cli.auth_user = self.auth_user.clone();
+        cli.token = self.token.clone();
+
+        Ok(cli)
+    }
+
+    /// Returns the KV client serializable reads are pinned to according to the read preference.
+    async fn pin_reads(&self) -> Result<Option<KvClient<Channel>>> {
+        let url = match self.cfg.read_preference {
+            ReadPreference::AnyMember => return Ok(None),
+            ReadPreference::Nearest => match self.cfg.endpoints.first() {
+                Some(e) => e.url.clone(),
+                None => return Ok(None),
+            },
+            ReadPreference::Leader => return Ok(Some(self.leader_kv_client().await?)),
+        };
+
+        Ok(Some(self.for_endpoint(url).await?.kv_client))
+    }
+
+    /// Returns a KV client connected to the current leader.
+    async fn leader_kv_client(&self) -> Result<KvClient<Channel>> {
+        let leader = self.leader_member().await?;
+        let url = match leader.client_urls.into_iter().next() {
+            Some(url) => url,
+            None => return Err(Error::LeaderNotFound(leader.id)),
+        };
+
+        Ok(self.for_endpoint(url).await?.kv_client)
+    }
+
+    /// Executes a write of the KV API, on the leader if writes are pinned to it.
+    ///
+    /// When the pinned member reports it is no longer the leader, the new leader is looked up
+    /// before the write is retried. Until one is found, writes go through all endpoints.
+    ///
+    /// Unless the request only reads, it is not retried when the leader changed after it was
+    /// proposed, as it may have been applied and retrying it could apply it twice.
+    async fn execute_write<F, Fut, T, R>(
+        &self,
+        req: tonic::Request<T>,
+        read_only: bool,
+        f: F,
+    ) -> Result<R>
+    where
+        F: Fn(KvClient<Channel>, tonic::Request<T>) -> Fut,
+        Fut: Future<Output = std::result::Result<R, Status>>,
+        T: Clone,
+    {
+        let f = &f;
+        self.execute(req, read_only, |req| async move {
+            let kv_client = match &*self.write_kv_client.read().await {
+                Some(kv_client) => kv_client.clone(),
+                None => self.kv_client.clone(),
+            };
+
+            let resp = f(kv_client, req).await;
+            if let Err(status) = &resp {
+                if self.cfg.pin_leader_for_writes && is_leader_change(status) {
+                    *self.write_kv_client.write().await = self.leader_kv_client().await.ok();
+                }
+            }
+            resp
+        })
+        .await
+    }
+
+    /// Authenticates with the credentials of the client, if any. While authentication is not
+    /// enabled on the cluster, no token is needed and none is set.
+    pub(crate) async fn refresh_token(&self) -> Result<()> {
+        if let Some((username, password)) = &self.auth_user {
+            let token = match self.authenticate((username, password)).await {
+                Ok(resp) => resp.token,
+                Err(Error::AuthNotEnabled(_)) => return Ok(()),
+                Err(e) => {
+                    return Err(Error::AuthFailed {
+                        username: username.clone(),
+                        source: Box::new(e),
+                    })
+                }
+            };
+            self.set_metadata_token(&token).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn set_metadata_token(&self, token: &str) -> Result<()> {
+        let t = match MetadataValue::try_from(token) {
+            Ok(t) => t,
+            Err(err) => return Err(Error::ParseMetadataToken(err.to_string())),
+        };
+        let mut x = self.token.write().await;
+        *x = Some(t);
+
+        Ok(())
+    }
+
+    /// Whether all the endpoints of the client are connected to with TLS.
+    pub fn is_tls(&self) -> bool {
+        !self.cfg.endpoints.is_empty() && self.cfg.endpoints.iter().all(Endpoint::is_tls)
+    }
+
+    /// Returns the name of the user the client authenticates as, if any.
+    pub fn username(&self) -> Option<&str> {
+        self.auth_user
+            .as_ref()
+            .map(|(username, _)| username.as_str())
+    }
+
+    /// Whether requests are made on behalf of a user, with credentials or a pre-obtained token.
+    fn has_credentials(&self) -> bool {
+        self.auth_user.is_some() || self.cfg.token.is_some()
+    }
+
+    async fn set_token<T>(&self, req: &mut tonic::Request<T>) {
+        let token = self.token.clone();
+        let h = token.read().await;
+        if let Some(token) = h.to_owned() {
+            req.metadata_mut()
+                .insert(self.cfg.auth_header_name.clone(), token);
+        }
+    }
+
+    /// Rejects requests the server would refuse for exceeding its maximum request size.
+    fn check_request_size(&self, req: &impl prost::Message) -> Result<()> {
+        let size = req.encoded_len();
+        let limit = self.cfg.max_request_bytes;
+        if size > limit {
+            return Err(Error::RequestTooLarge { size, limit });
+        }
+
+        Ok(())
+    }
+
+    async fn execute_with_retries<F, Fut, T, R>(&self, req: tonic::Request<T>, f: F) -> Result<R>
+    where
+        F: Fn(tonic::Request<T>) -> Fut,
+        Fut: Future<Output = std::result::Result<R, Status>>,
+        T: Clone,
+    {
+        self.execute(req, true, f).await
+    }
+
+    /// Executes the request through the circuit breaker, retrying it on failures it can recover
+    /// from. Failures after which the request may have been applied are only retried if it is
+    /// idempotent.
+    async fn execute<F, Fut, T, R>(
+        &self,
+        req: tonic::Request<T>,
+        idempotent: bool,
+        f: F,
+    ) -> Result<R>
+    where
+        F: Fn(tonic::Request<T>) -> Fut,
+        Fut: Future<Output = std::result::Result<R, Status>>,
+        T: Clone,
+    {
+        let breaker = match &self.breaker {
+            Some(breaker) => breaker,
+            None => return self.retry(req, idempotent, f).await,
+        };
+
+        breaker.check()?;
+        let resp = self.retry(req, idempotent, f).await;
+
+        let unhealthy = match &resp {
+            Ok(_) => false,
+            Err(Error::ExecuteFailed { .. } | Error::DeadlineExceeded(_)) => true,
+            Err(e) => e
+                .status()
+                .is_some_and(|status| status.code() == tonic::Code::Unavailable),
+        };
+        if unhealthy {
+            breaker.record_failure()?;
+        } else {
+            breaker.record_success()?;
+        }
+
+        resp
+    }
+
+    async fn retry<F, Fut, T, R>(&self, req: tonic::Request<T>, idempotent: bool, f: F) -> Result<R>
+    where
+        F: Fn(tonic::Request<T>) -> Fut,
+        Fut: Future<Output = std::result::Result<R, Status>>,
+        T: Clone,
+    {
+        let mut last_status = None;
+        for attempt in 0..=self.max_retries {
+            let mut new_req = tonic::Request::new(req.get_ref().clone());
+            self.set_token(&mut new_req).await;
+            if let Some(timeout) = self.cfg.request_timeout {
+                new_req.set_timeout(timeout);
+            }
+
+            match f(new_req).await {
+                Ok(response) => {
+                    return Ok(response);
+                }
+                Err(status) if self.max_retries == 0 => {
+                    return Err(status.into());
+                }
+                Err(status) => {
+                    // Retrying a request that may have been applied could apply a write twice.
+                    if !idempotent && is_ambiguous_leader_change(&status) {
+                        return Err(status.into());
+                    }
+
+                    if status.code() == tonic::Code::Unauthenticated {
+                        self.refresh_token().await?;
+                    } else if status.code() != tonic::Code::Unavailable
+                        && !is_leader_change(&status)
+                    {
+                        return Err(status.into());
+                    }
+
+                    if attempt < self.max_retries {
+                        if let Some(RetryHook(hook)) = &self.cfg.on_retry {
+                            hook(attempt + 1, status.code());
+                        }
+                    }
+                    last_status = Some(Box::new(status));
+                }
+            }
+        }
+        Err(Error::ExecuteFailed { last_status })
+    }
+
+    /// Creates the watches over a single stream, and returns their cancelers in the same order.
+    async fn open_watches(
+        &self,
+        reqs: Vec<WatchCreateRequest>,
+    ) -> Result<(WatchStream, Vec<WatchCanceler>)> {
+        // All create requests are queued before the stream is opened.
+        let capacity = reqs.len().max(self.cfg.watch_buffer);
+        let (tx, rx) = channel::<etcdserverpb::WatchRequest>(capacity);
+
+        let count = reqs.len();
+        let replays_from = reqs
+            .iter()
+            .map(|req| req.replays_from())
+            .filter(|rev| *rev > 0)
+            .min();
+        let require_leader = reqs.iter().any(|req| req.requires_leader());
+        for req in reqs {
+            tx.send(req.into()).await?;
+        }
+
+        let mut req = tonic::Request::new(ReceiverStream::new(rx));
+        self.refresh_token().await?;
+        self.set_token(&mut req).await;
+
+        if require_leader {
+            req.metadata_mut()
+                .insert("hasleader", "true".try_into().unwrap());
+        }
+
+        let resp = self.watch_client.clone().watch(req).await?;
+
+        let mut inbound = resp.into_inner();
+
+        let mut cancelers = Vec::with_capacity(count);
+        let mut pending = VecDeque::new();
+        let mut created_revision = 0;
+        while cancelers.len() < count {
+            let watch_id = match inbound.message().await? {
+                Some(resp) => {
+                    // The watches already created may receive events before the next one is.
+                    if !resp.created {
+                        pending.push_back(resp);
+                        continue;
+                    }
+                    if resp.canceled {
+                        if resp.cancel_reason.contains("duplicate watch ID") {
+                            return Err(Error::DuplicateWatchId(resp.watch_id));
+                        }
+                        return Err(Error::WatchEvent(resp.cancel_reason));
+                    }
+                    if !resp.events.is_empty() {
+                        return Err(Error::WatchEvent(format!(
+                            "created response carries events: {resp:?}"
+                        )));
+                    }
+                    if let Some(header) = &resp.header {
+                        created_revision = created_revision.max(header.revision);
+                    }
+                    resp.watch_id
+                }
+
+                None => return Err(Error::CreateWatch),
+            };
+            cancelers.push(WatchCanceler::new(watch_id, tx.clone()));
+        }
+
+        // Watches starting after the creation have no backlog to replay.
+        let caught_up = replays_from.map_or(true, |rev| rev > created_revision);
+        if !caught_up {
+            // The range may have nothing left to replay, in which case no event tells the replay
+            // is over.
+            tx.send(progress_request()).await?;
+        }
+
+        Ok((
+            WatchStream::new(inbound, pending, count, created_revision, caught_up, tx),
+            cancelers,
+        ))
+    }
+
+    /// Watches the key range from the given revision, or from now if it is 0, and keeps watching
+    /// after the authentication token expires. See [`ResumableWatch`].
+    pub async fn watch_resumable(
+        &self,
+        key_range: KeyRange,
+        start_revision: i64,
+    ) -> Result<ResumableWatch> {
+        ResumableWatch::new(self.clone(), key_range, start_revision).await
+    }
+
+    /// Fetches the current key-values under the prefix and watches for changes made after them,
+    /// recovering from compactions by reading the prefix again. See [`ReconcilingWatch`].
+    ///
+    /// This is the opt-in recovering mode of [`Client::watch_prefix_with_current`]: instead of
+    /// ending with `Error::WatchCanceled`, the watch yields a `Reset` and resumes, so its items
+    /// are `PrefixWatchEvent`s rather than events.
+    pub async fn watch_prefix_reconciling<K>(
+        &self,
+        prefix: K,
+    ) -> Result<(Vec<KeyValue>, ReconcilingWatch)>
+    where
+        K: Into<Vec<u8>>,
+    {
+        ReconcilingWatch::new(self.clone(), prefix.into()).await
+    }
+
+    /// Fetches the current key-values under the prefix and watches for changes made after them.
+    ///
+    /// No change is missed while the events stream on. If the stream ends, `WatchEvents::error`
+    /// tells why, e.g. a compaction of the revisions it was about to deliver. To recover from
+    /// compactions instead, see [`Client::watch_prefix_reconciling`].
+    pub async fn watch_prefix_with_current<K>(
+        &self,
+        prefix: K,
+    ) -> Result<(Vec<KeyValue>, WatchEvents)>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let key_range = KeyRange::prefix(prefix);
+        let resp = self.get(key_range.clone()).await?;
+
+        // Start right after the revision the snapshot was read at, so that no event is missed or
+        // delivered twice.
+        let req = WatchCreateRequest::create(key_range).start_revision(resp.header.revision() + 1);
+        let (stream, canceler) = self.watch(req).await?;
+
+        Ok((resp.kvs, WatchEvents::new(stream, canceler)))
+    }
+
+    /// Watches the additions and removals of keys under the prefix, seeded with the current keys.
+    pub async fn watch_prefix_membership<K>(&self, prefix: K) -> Result<MembershipChanges>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let (kvs, events) = self.watch_prefix_with_current(prefix).await?;
+
+        Ok(events.membership_changes(kvs.into_iter().map(|kv| kv.key)))
+    }
+
+    /// Watches several key ranges over a single stream.
+    ///
+    /// The responses of every watch are merged into the returned stream, and can be told apart by
+    /// their `watch_id`, which is the one of the canceler at the same index as the range. The
+    /// cancellation of a watch is received as a response, and the stream closes once the last
+    /// watch is canceled.
+    pub async fn watch_ranges(
+        &self,
+        ranges: Vec<KeyRange>,
+    ) -> Result<(WatchStream, Vec<WatchCanceler>)> {
+        if ranges.is_empty() {
+            return Err(Error::CreateWatch);
+        }
+
+        let reqs = ranges.into_iter().map(WatchCreateRequest::create).collect();
+        self.open_watches(reqs).await
+    }
+
+    /// Returns the member which is currently the leader of the cluster.
+    ///
+    /// # Errors
+    /// Will returns `Err` if the reported leader is not a member of the cluster.
+    pub async fn leader_member(&self) -> Result<Member> {
+        let leader = self.status().await?.leader;
+
+        self.member_list()
+            .await?
+            .members
+            .into_iter()
+            .find(|m| m.id == leader)
+            .ok_or(Error::LeaderNotFound(leader))
+    }
+
+    /// Whether the key exists, including when it holds an empty value.
+    pub async fn exists<K>(&self, key: K) -> Result<bool>
+    where
+        K: Into<Vec<u8>>,
+    {
+        Ok(self.count_matching(KeyRange::key(key)).await? > 0)
+    }
+
+    /// Returns the keys under the prefix, decoded as UTF-8 with invalid sequences replaced, without
+    /// fetching their values. See `list_keys_bytes` for binary keys.
+    pub async fn list_keys<K>(&self, prefix: K) -> Result<Vec<String>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        Ok(self
+            .list_keys_bytes(prefix)
+            .await?
+            .into_iter()
+            .map(|key| match String::from_utf8(key) {
+                Ok(key) => key,
+                Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+            })
+            .collect())
+    }
+
+    /// Returns the keys under the prefix, without fetching their values.
+    pub async fn list_keys_bytes<K>(&self, prefix: K) -> Result<Vec<Vec<u8>>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let resp = self
+            .get(RangeRequest::new(KeyRange::prefix(prefix)).keys_only())
+            .await?;
+
+        Ok(resp.kvs.into_iter().map(|kv| kv.key).collect())
+    }
+
+    /// Returns the smallest key under the prefix, or `None` if the prefix holds no key, without
+    /// fetching the other keys or any value.
+    pub async fn first_key<K>(&self, prefix: K) -> Result<Option<Vec<u8>>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        self.boundary_key(prefix, SortOrder::Ascending).await
+    }
+
+    /// Returns the largest key under the prefix, or `None` if the prefix holds no key, without
+    /// fetching the other keys or any value.
+    pub async fn last_key<K>(&self, prefix: K) -> Result<Option<Vec<u8>>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        self.boundary_key(prefix, SortOrder::Descending).await
+    }
+
+    async fn boundary_key<K>(&self, prefix: K, order: SortOrder) -> Result<Option<Vec<u8>>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let req = RangeRequest::new(KeyRange::prefix(prefix))
+            .sort_by_key(order)
+            .limit(1)
+            .keys_only();
+
+        Ok(self.get(req).await?.kvs.pop().map(|kv| kv.key))
+    }
+
+    /// Returns how many keys the range holds, e.g. to check what a delete would remove before
+    /// calling `delete_by_prefix`.
+    pub async fn count_matching(&self, key_range: KeyRange) -> Result<u64> {
+        Ok(self
+            .get(RangeRequest::new(key_range).count_only())
+            .await?
+            .count)
+    }
+
+    /// Deletes the keys of the range, unless there are more than `limit` of them.
+    ///
+    /// The keys are counted first, and the delete only applies if no key of the range was created
+    /// or modified since, so that the limit holds even with concurrent writers.
+    ///
+    /// # Errors
+    /// Will return `Err(Error::DeleteTooLarge)` if the range holds more than `limit` keys, and
+    /// `Err(Error::TooManyConflicts)` if the range kept being modified concurrently.
+    pub async fn delete_range_limited(
+        &self,
+        key_range: KeyRange,
+        limit: u64,
+    ) -> Result<DeleteResponse> {
+        for _ in 0..CAS_MAX_ATTEMPTS {
+            let resp = self
+                .get(RangeRequest::new(key_range.clone()).count_only())
+                .await?;
+            if resp.count > limit {
+                return Err(Error::DeleteTooLarge {
+                    count: resp.count,
+                    limit,
+                });
+            }
+
+            let req = TxnRequest::new()
+                .when(TxnCompare::mod_revision(
+                    key_range.clone(),
+                    TxnCmp::Less,
+                    resp.header.revision() + 1,
+                ))
+                .and_then(DeleteRequest::new(key_range.clone()));
+
+            let resp = self.txn(req).await?;
+            if resp.succeeded {
+                if let Some(TxnOpResponse::Delete(resp)) = resp.responses.into_iter().next() {
+                    return Ok(resp);
+                }
+                return Err(Error::ExecuteFailed { last_status: None });
+            }
+        }
+
+        Err(Error::TooManyConflicts(CAS_MAX_ATTEMPTS))
+    }
+
+    /// Returns the current revision of the key-value store, without fetching any key.
+    pub async fn current_revision(&self) -> Result<i64> {
+        let resp = self
+            .get(RangeRequest::new(KeyRange::key([0])).count_only())
+            .await?;
+
+        Ok(resp.header.revision())
+    }
+
+    /// Returns the revision the key-value store was last compacted at, or 0 if it was never
+    /// compacted. Watches and reads can still start from any revision from this one on, so a
+    /// watcher can tell whether it can resume from its saved revision or has to reconcile.
+    ///
+    /// A watch from a compacted revision is canceled with the compaction revision, which is how
+    /// it is found. Since a watch from a revision which is not compacted would replay the history
+    /// instead, a read at the first revision checks first whether there was a compaction.
+    pub async fn compact_revision(&self) -> Result<i64> {
+        let req = RangeRequest::new(KeyRange::key([0]))
+            .count_only()
+            .revision(1);
+        match self.get(req).await {
+            Ok(_) => return Ok(0),
+            Err(e) if e.status().map(|status| status.code()) == Some(tonic::Code::OutOfRange) => {}
+            Err(e) => return Err(e),
+        }
+
+        let req = WatchCreateRequest::create(KeyRange::key([0])).start_revision(1);
+        let (mut stream, _canceler) = self.watch(req).await?;
+        loop {
+            match stream.inbound().await {
+                // Progress notifications may come before the cancellation.
+                WatchInbound::Ready(_) => {}
+                WatchInbound::Interrupted(e) => return Err(e),
+                WatchInbound::Closed => {
+                    return stream.compact_revision().ok_or_else(|| {
+                        Error::WatchEvent(format!(
+                            "watch from a compacted revision was canceled: {}",
+                            stream.cancel_reason().unwrap_or_default()
+                        ))
+                    })
+                }
+            }
+        }
+    }
+
+    /// Puts the key-value unless the key was modified after `mod_revision`, and returns whether a
+    /// write occurred. This is a single compare-and-swap attempt for a caller which already knows
+    /// the revision of its last read. A missing key counts as never modified, and is created.
+    pub async fn put_if_unchanged_since<K, V>(
+        &self,
+        key: K,
+        value: V,
+        mod_revision: i64,
+    ) -> Result<bool>
+    where
+        K: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let key = key.into();
+        let req = TxnRequest::new()
+            .when(TxnCompare::mod_revision(
+                KeyRange::key(key.clone()),
+                TxnCmp::Less,
+                mod_revision + 1,
+            ))
+            .and_then(PutRequest::new(key, value));
+
+        Ok(self.txn(req).await?.succeeded)
+    }
+
+    /// Atomically swaps the values of the two keys, and returns whether the swap occurred. A
+    /// missing key swaps as a deletion of the other key. Returns `false` if either key was
+    /// modified between the read and the swap. Swapping a key with itself changes nothing.
+    pub async fn swap<A, B>(&self, a: A, b: B) -> Result<bool>
+    where
+        A: Into<Vec<u8>>,
+        B: Into<Vec<u8>>,
+    {
+        let a = a.into();
+        let b = b.into();
+        // etcd rejects a transaction writing the same key twice.
+        if a == b {
+            return Ok(true);
+        }
+
+        let read = TxnRequest::new()
+            .and_then(RangeRequest::new(KeyRange::key(a.clone())))
+            .and_then(RangeRequest::new(KeyRange::key(b.clone())));
+        let mut kvs = self
+            .txn(read)
+            .await?
+            .responses
+            .into_iter()
+            .map(|resp| match resp {
+                TxnOpResponse::Range(resp) => resp.kvs.into_iter().next(),
+                _ => None,
+            });
+        let (kv_a, kv_b) = (kvs.next().flatten(), kvs.next().flatten());
+
+        // A missing key has a mod_revision of 0 in compares.
+        let unchanged = |key: &[u8], kv: &Option<KeyValue>| {
+            TxnCompare::mod_revision(
+                KeyRange::key(key),
+                TxnCmp::Equal,
+                kv.as_ref().map_or(0, |kv| kv.mod_revision),
+            )
+        };
+        let write = |key: Vec<u8>, kv: Option<KeyValue>| -> TxnOp {
+            match kv {
+                Some(kv) => PutRequest::from_bytes(key, kv.value).into(),
+                None => DeleteRequest::new(KeyRange::key(key)).into(),
+            }
+        };
+
+        let req = TxnRequest::new()
+            .when(unchanged(&a, &kv_a))
+            .when(unchanged(&b, &kv_b))
+            .and_then(write(a, kv_b))
+            .and_then(write(b, kv_a));
+
+        Ok(self.txn(req).await?.succeeded)
+    }
+
+    /// Puts the key-value unless the key already holds the same value, and returns whether a write
+    /// occurred. Skipping identical writes avoids bumping the revision and notifying watchers.
+    pub async fn put_if_changed<K, V>(&self, key: K, value: V) -> Result<bool>
+    where
+        K: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let key = key.into();
+        let value = value.into();
+
+        // A value compare never holds for a missing key, so the key is created in the failure
+        // branch if it does not exist yet.
+        let create = TxnRequest::new()
+            .when(TxnCompare::create_revision(
+                KeyRange::key(key.clone()),
+                TxnCmp::Equal,
+                0,
+            ))
+            .and_then(PutRequest::new(key.clone(), value.clone()));
+        let req = TxnRequest::new()
+            .when(TxnCompare::value(
+                KeyRange::key(key.clone()),
+                TxnCmp::NotEqual,
+                value.clone(),
+            ))
+            .and_then(PutRequest::new(key, value))
+            .or_else(create);
+
+        let resp = self.txn(req).await?;
+        if resp.succeeded {
+            return Ok(true);
+        }
+
+        Ok(resp.responses.into_iter().any(|resp| match resp {
+            TxnOpResponse::Txn(txn) => txn.succeeded,
+            _ => false,
+        }))
+    }
+
+    /// Keeps the lease alive from a background task, and replaces it once it is lost.
+    ///
+    /// When the lease expires or is revoked, a new lease with the given TTL is granted and the puts
+    /// are made again attached to it. The returned handle then reports a `LeaseRegranted` event
+    /// with the new lease ID, so that callers relying on the lease identity can follow it.
+    pub async fn keep_alive_regranting(
+        &self,
+        lease_id: LeaseId,
+        ttl: Duration,
+        puts: Vec<PutRequest>,
+    ) -> Result<AutoKeepAlive> {
+        let mut keep_alive = self.keep_alive_for(lease_id).await?;
+        let (state_tx, state_rx) = watch::channel((lease_id, None));
+        let (regranted_tx, regranted_rx) = unbounded_channel();
+        let cli = self.clone();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let id = keep_alive.lease_id();
+                match keep_alive.keep_alive().await {
+                    Ok(Some(resp)) if resp.ttl > 0 => {
+                        state_tx.send_replace((id, Some(resp.ttl)));
+                        tokio::time::sleep(Duration::from_secs(resp.ttl as u64) / 3).await;
+                        continue;
+                    }
+                    Ok(Some(_)) => {}
+                    Ok(None) | Err(_) => {
+                        // A broken stream does not mean the lease is lost, so the lease is
+                        // reattached first and only regranted if the server reports it expired.
+                        match cli.keep_alive_for(id).await {
+                            Ok(reopened) => keep_alive = reopened,
+                            Err(_) => tokio::time::sleep(REGRANT_RETRY_INTERVAL).await,
+                        }
+                        continue;
+                    }
+                }
+
+                state_tx.send_replace((id, Some(0)));
+                match cli.regrant(ttl, &puts).await {
+                    Ok(mut regranted) => {
+                        let lease_id = regranted.lease_id();
+                        keep_alive = regranted;
+                        let _ = regranted_tx.send(LeaseRegranted {
+                            previous: id,
+                            lease_id,
+                        });
+                    }
+                    Err(_) => tokio::time::sleep(REGRANT_RETRY_INTERVAL).await,
+                }
+            }
+        });
+
+        Ok(AutoKeepAlive::new(
+            self.clone(),
+            state_rx,
+            Some(regranted_rx),
+            task,
+        ))
+    }
+
+    /// Grants a lease and puts all entries attached to it in a single transaction, then opens the
+    /// keep-alive stream of the lease.
+    ///
+    /// The entries are all written or none are. If writing them or opening the keep-alive stream
+    /// fails, the lease is revoked, which deletes the entries already written.
+    pub async fn register<K, V>(
+        &self,
+        ttl: Duration,
+        entries: Vec<(K, V)>,
+    ) -> Result<(LeaseId, LeaseKeepAlive)>
+    where
+        K: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let lease_id = self.grant_lease(LeaseGrantRequest::new(ttl)).await?.id;
+
+        let req = entries.into_iter().fold(TxnRequest::new(), |req, (k, v)| {
+            req.and_then(PutRequest::new(k, v).lease(lease_id))
+        });
+        let keep_alive = match self.txn(req).await {
+            Ok(_) => self.keep_alive_for(lease_id).await,
+            Err(e) => Err(e),
+        };
+
+        match keep_alive {
+            Ok(keep_alive) => Ok((lease_id, keep_alive)),
+            Err(e) => {
+                let _ = self.revoke(LeaseRevokeRequest::new(lease_id)).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Grants a lease with the given TTL and keeps it alive in the background, for locks and
+    /// campaigns to be attached to.
+    pub async fn session(&self, ttl: Duration) -> Result<Session> {
+        let lease_id = self.grant_lease(LeaseGrantRequest::new(ttl)).await?.id;
+        let keep_alive = self.keep_alive_for(lease_id).await?.spawn();
+
+        Ok(Session::new(self.clone(), keep_alive, None))
+    }
+
+    /// Resumes a session detached from another client, possibly in another process, by keeping
+    /// its lease alive from this client.
+    ///
+    /// The leadership of the state is kept only if its key still exists and is attached to the
+    /// lease; otherwise the resumed session has no leader.
+    ///
+    /// # Errors
+    /// Will return `Err(Error::SessionExpired)` if the lease has expired or was revoked.
+    pub async fn resume_session(&self, state: SessionState) -> Result<Session> {
+        if state.expired {
+            return Err(Error::SessionExpired(state.lease_id));
+        }
+
+        let ttl = self
+            .time_to_live(LeaseTimeToLiveRequest::new(state.lease_id))
+            .await?
+            .ttl;
+        if ttl <= 0 {
+            return Err(Error::SessionExpired(state.lease_id));
+        }
+
+        let keep_alive = self.keep_alive_for(state.lease_id).await?.spawn();
+
+        let leader = match state.leader {
+            Some(leader) => {
+                let kv = self
+                    .get(KeyRange::key(leader.key.clone()))
+                    .await?
+                    .kvs
+                    .into_iter()
+                    .next();
+                kv.filter(|kv| kv.create_revision == leader.rev && kv.lease == state.lease_id)
+                    .map(|_| leader)
+            }
+            None => None,
+        };
+
+        Ok(Session::new(self.clone(), keep_alive, leader))
+    }
+
+    /// Grants a new lease, attaches the puts to it, and opens its keep-alive stream.
+    async fn regrant(&self, ttl: Duration, puts: &[PutRequest]) -> Result<LeaseKeepAlive> {
+        let lease_id = self.grant_lease(LeaseGrantRequest::new(ttl)).await?.id;
+        for put in puts {
+            self.put(put.clone().lease(lease_id)).await?;
+        }
+
+        self.keep_alive_for(lease_id).await
+    }
+
+    /// Returns the keys attached to the lease, which are deleted once it is revoked or expires.
+    pub async fn lease_keys(&self, lease_id: LeaseId) -> Result<Vec<Vec<u8>>> {
+        let req = LeaseTimeToLiveRequest::new(lease_id).with_keys(true);
+
+        Ok(self.time_to_live(req).await?.keys)
+    }
+
+    /// Revokes the lease, and returns the keys which were attached to it right before.
+    ///
+    /// The keys are fetched before revoking, so a key attached in between is deleted without
+    /// being reported.
+    pub async fn revoke_and_report(&self, lease_id: LeaseId) -> Result<Vec<Vec<u8>>> {
+        let keys = self.lease_keys(lease_id).await?;
+        self.revoke(LeaseRevokeRequest::new(lease_id)).await?;
+
+        Ok(keys)
+    }
+
+    /// Returns the number of existing leases.
+    pub async fn lease_count(&self) -> Result<usize> {
+        Ok(self.lease_leases().await?.leases.len())
+    }
+
+    /// Returns the number of existing leases along with the backend size of the member serving
+    /// the requests, for capacity planning.
+    pub async fn lease_stats(&self) -> Result<LeaseStats> {
+        let (leases, status) = futures::try_join!(self.lease_count(), self.status())?;
+
+        Ok(LeaseStats {
+            leases,
+            db_size: status.db_size,
+            db_size_in_use: status.db_size_in_use,
+        })
+    }
+
+    /// Revokes every lease whose attached keys match the predicate, and returns how many leases
+    /// were revoked.
+    ///
+    /// A lease which expires or is revoked by someone else while iterating is skipped.
+    pub async fn revoke_leases_where<F>(&self, pred: F) -> Result<usize>
+    where
+        F: Fn(&[Vec<u8>]) -> bool,
+    {
+        let mut revoked = 0;
+
+        for lease_id in self.lease_leases().await?.leases {
+            let keys = match self.lease_keys(lease_id).await {
+                Ok(keys) => keys,
+                Err(Error::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            if !pred(&keys) {
+                continue;
+            }
+
+            match self.revoke(LeaseRevokeRequest::new(lease_id)).await {
+                Ok(_) => revoked += 1,
+                Err(Error::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(revoked)
+    }
+
+    /// Streams the key-values of the range, fetching them by pages of `page_size` keys.
+    ///
+    /// All pages are read at the revision of the first one, so the stream is a consistent snapshot
+    /// of the range, as long as that revision is not compacted while scanning. The stream ends
+    /// after the first error.
+    pub fn scan(
+        &self,
+        key_range: KeyRange,
+        page_size: u64,
+    ) -> impl Stream<Item = Result<KeyValue>> + '_ {
+        let range_end = key_range.range_end;
+        let pages = (Some(key_range.key), 0, VecDeque::new());
+
+        stream::unfold(pages, move |(mut next_key, mut revision, mut kvs)| {
+            let range_end = range_end.clone();
+            async move {
+                loop {
+                    if let Some(kv) = kvs.pop_front() {
+                        return Some((Ok(kv), (next_key, revision, kvs)));
+                    }
+
+                    let key = next_key.take()?;
+                    let req = RangeRequest::new(KeyRange::range(key, range_end.clone()))
+                        .limit(page_size)
+                        .revision(revision);
+
+                    let resp = match self.get(req).await {
+                        Ok(resp) => resp,
+                        Err(e) => return Some((Err(e), (None, revision, kvs))),
+                    };
+
+                    revision = resp.header.revision();
+                    if resp.has_more {
+                        next_key = resp.kvs.last().map(|kv| {
+                            let mut key = kv.key.clone();
+                            key.push(0);
+                            key
+                        });
+                    }
+                    kvs.extend(resp.kvs);
+                }
+            }
+        })
+    }
+
+    /// Watches a single key, and streams its transitions as `(previous value, new value)` pairs,
+    /// where a `None` previous value means the key was created and a `None` new value means it was
+    /// deleted.
+    ///
+    /// The previous value of the first transition is seeded from a read of the key, and the watch
+    /// starts right after that read, so that no transition is missed. The stream ends after the
+    /// first error, or once the watch is canceled.
+    pub fn watch_key_transitions<K>(&self, key: K) -> impl Stream<Item = Result<KeyTransition>> + '_
+    where
+        K: Into<Vec<u8>>,
+    {
+        enum State {
+            Init,
+            Watching {
+                stream: Box<WatchStream>,
+                canceler: WatchCanceler,
+                prev: Option<Vec<u8>>,
+                events: VecDeque<Event>,
+            },
+            Done,
+        }
+
+        let key = key.into();
+
+        stream::unfold(State::Init, move |state| {
+            let key = key.clone();
+            async move {
+                let (mut stream, canceler, mut prev, mut events) = match state {
+                    State::Init => {
+                        let watch = async {
+                            let resp = self.get(KeyRange::key(key.clone())).await?;
+                            let prev = resp.kvs.into_iter().next().map(|kv| kv.value.into());
+
+                            let req = WatchCreateRequest::create(KeyRange::key(key))
+                                .start_revision(resp.header.revision() + 1)
+                                .prev_kv();
+                            let (stream, canceler) = self.watch(req).await?;
+
+                            Ok((stream, canceler, prev))
+                        };
+                        match watch.await {
+                            Ok((stream, canceler, prev)) => {
+                                (Box::new(stream), canceler, prev, VecDeque::new())
+                            }
+                            Err(e) => return Some((Err(e), State::Done)),
+                        }
+                    }
+                    State::Watching {
+                        stream,
+                        canceler,
+                        prev,
+                        events,
+                    } => (stream, canceler, prev, events),
+                    State::Done => return None,
+                };
+
+                loop {
+                    if let Some(event) = events.pop_front() {
+                        // The tracked value stands in for a previous key-value which is not
+                        // available anymore.
+                        let old = match event.prev_kv {
+                            Some(prev_kv) => Some(prev_kv.value.into()),
+                            None => prev.take(),
+                        };
+                        let new: Option<Vec<u8>> = match event.event_type {
+                            EventType::Delete => None,
+                            _ => Some(event.kv.value.into()),
+                        };
+                        prev.clone_from(&new);
+
+                        let state = State::Watching {
+                            stream,
+                            canceler,
+                            prev,
+                            events,
+                        };
+                        return Some((Ok((old, new)), state));
+                    }
+
+                    match stream.inbound().await {
+                        WatchInbound::Ready(resp) => events.extend(resp.events),
+                        WatchInbound::Interrupted(e) => return Some((Err(e), State::Done)),
+                        WatchInbound::Closed => return None,
+                    }
+                }
+            }
+        })
+    }
+
+    /// Watches as requested, and streams the events grouped by revision, so that the events of
+    /// one transaction can be applied atomically. See `WatchEvents` for single events.
+    ///
+    /// The stream ends after the first error, or once the watch is canceled.
+    pub fn watch_batches<R>(&self, req: R) -> impl Stream<Item = Result<EventBatch>> + '_
+    where
+        R: Into<WatchCreateRequest>,
+    {
+        enum State {
+            Init(WatchCreateRequest),
+            Watching {
+                stream: Box<WatchStream>,
+                canceler: WatchCanceler,
+                batches: VecDeque<EventBatch>,
+            },
+            Done,
+        }
+
+        stream::unfold(State::Init(req.into()), move |state| async move {
+            let (mut stream, canceler, mut batches) = match state {
+                State::Init(req) => match self.watch(req).await {
+                    Ok((stream, canceler)) => (Box::new(stream), canceler, VecDeque::new()),
+                    Err(e) => return Some((Err(e), State::Done)),
+                },
+                State::Watching {
+                    stream,
+                    canceler,
+                    batches,
+                } => (stream, canceler, batches),
+                State::Done => return None,
+            };
+
+            loop {
+                if let Some(batch) = batches.pop_front() {
+                    let state = State::Watching {
+                        stream,
+                        canceler,
+                        batches,
+                    };
+                    return Some((Ok(batch), state));
+                }
+
+                match stream.inbound().await {
+                    WatchInbound::Ready(resp) => batches.extend(EventBatch::group(resp.events)),
+                    WatchInbound::Interrupted(e) => return Some((Err(e), State::Done)),
+                    WatchInbound::Closed => return None,
+                }
+            }
+        })
+    }
+
+    /// Atomically moves the value of a key to another key, keeping its lease.
+    ///
+    /// Returns `false` if the old key does not exist or was modified concurrently, in which case
+    /// nothing is changed. An existing value of the new key is overwritten. Renaming a key to
+    /// itself changes nothing, and only returns whether the key exists.
+    pub async fn rename<O, N>(&self, old: O, new: N) -> Result<bool>
+    where
+        O: Into<Vec<u8>>,
+        N: Into<Vec<u8>>,
+    {
+        let old = old.into();
+        let new = new.into();
+        let kv = match self.get(KeyRange::key(old.clone())).await?.kvs.pop() {
+            Some(kv) => kv,
+            None => return Ok(false),
+        };
+        // etcd rejects a transaction writing the same key twice.
+        if old == new {
+            return Ok(true);
+        }
+
+        let req = TxnRequest::new()
+            .when(TxnCompare::mod_revision(
+                KeyRange::key(old.clone()),
+                TxnCmp::Equal,
+                kv.mod_revision,
+            ))
+            .and_then(PutRequest::new(new, kv.value).lease(kv.lease))
+            .and_then(DeleteRequest::new(KeyRange::key(old)));
+
+        Ok(self.txn(req).await?.succeeded)
+    }
+
+    /// Returns the keys under the prefix modified after the given revision, ordered by
+    /// modification: a put event with the current value of each existing key, and a delete event
+    /// for each key deleted since.
+    ///
+    /// The current values are read with a range, and the deletions are caught up with a watch of
+    /// the prefix from `since_revision + 1` to the revision of the range. If that revision is
+    /// compacted, the deletions cannot be known, and only the put events are returned.
+    ///
+    /// With servers before v3.4.24 and v3.5.8, the catch-up may only end at the next change of the
+    /// prefix, see [`WatchStream::caught_up`].
+    pub async fn changes_since<K>(&self, prefix: K, since_revision: i64) -> Result<Vec<Event>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let key_range = KeyRange::prefix(prefix);
+        let req = RangeRequest::new(key_range.clone()).min_mod_revision(since_revision + 1);
+        let resp = self.get(req).await?;
+        let revision = resp.header.revision();
+
+        let mut events: Vec<Event> = resp
+            .kvs
+            .into_iter()
+            .map(|kv| Event {
+                event_type: EventType::Put,
+                kv,
+                prev_kv: None,
+            })
+            .collect();
+
+        if revision > since_revision {
+            let existing: HashSet<Vec<u8>> = events.iter().map(|e| e.kv.key.clone()).collect();
+            let deletes = self
+                .deletes_between(key_range, since_revision + 1, revision)
+                .await?;
+            // A key deleted and put again exists, and its put event is enough.
+            events.extend(
+                deletes
+                    .into_values()
+                    .filter(|event| !existing.contains(&event.kv.key)),
+            );
+        }
+
+        events.sort_by_key(|event| event.kv.mod_revision);
+        Ok(events)
+    }
+
+    /// Returns the last deletion of each key of the range between the revisions, inclusive, by
+    /// key. Nothing is returned if the start revision is compacted.
+    async fn deletes_between(
+        &self,
+        key_range: KeyRange,
+        start_revision: i64,
+        end_revision: i64,
+    ) -> Result<HashMap<Vec<u8>, Event>> {
+        let req = WatchCreateRequest::create(key_range).start_revision(start_revision);
+        let (mut stream, _canceler) = self.watch(req).await?;
+
+        // The watch is created after the range was read, so it has caught up once it has
+        // replayed up to the end revision.
+        let mut deletes = HashMap::new();
+        while !stream.caught_up() {
+            match stream.inbound().await {
+                WatchInbound::Ready(resp) => {
+                    for event in resp.events {
+                        if event.is_delete() && event.kv.mod_revision <= end_revision {
+                            deletes.insert(event.kv.key.clone(), event);
+                        }
+                    }
+                }
+                WatchInbound::Interrupted(e) => return Err(e),
+                WatchInbound::Closed if stream.compact_revision().is_some() => {
+                    return Ok(HashMap::new())
+                }
+                WatchInbound::Closed => {
+                    return Err(Error::WatchEvent(format!(
+                        "watch of the deletions was canceled: {}",
+                        stream.cancel_reason().unwrap_or_default()
+                    )))
+                }
+            }
+        }
+
+        Ok(deletes)
+    }
+
+    /// Adds `delta` to the integer stored as a decimal string under the key, and returns the new
+    /// value. A missing key counts as 0, and the lease of an existing key is kept.
+    ///
+    /// # Errors
+    /// Will return `Err(Error::InvalidCounter)` if the value is not an integer,
+    /// `Err(Error::CounterOverflow)` if the new value does not fit in an `i64`, and
+    /// `Err(Error::TooManyConflicts)` if the key kept being modified concurrently.
+    pub async fn increment<K>(&self, key: K, delta: i64) -> Result<i64>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let key = key.into();
+        let mut current = self.get(KeyRange::key(key.clone())).await?.kvs.pop();
+
+        for _ in 0..CAS_MAX_ATTEMPTS {
+            let (value, mod_revision, lease) = match &current {
+                Some(kv) => {
+                    let value = std::str::from_utf8(&kv.value)
+                        .ok()
+                        .and_then(|value| value.parse::<i64>().ok())
+                        .ok_or_else(|| {
+                            Error::InvalidCounter(String::from_utf8_lossy(&key).into_owned())
+                        })?;
+                    (value, kv.mod_revision, kv.lease)
+                }
+                None => (0, 0, 0),
+            };
+            let next = value.checked_add(delta).ok_or_else(|| {
+                Error::CounterOverflow(String::from_utf8_lossy(&key).into_owned())
+            })?;
+
+            // A missing key has a mod revision of 0. On conflict, the current key-value is read
+            // in the same transaction.
+            let req = TxnRequest::new()
+                .when(TxnCompare::mod_revision(
+                    KeyRange::key(key.clone()),
+                    TxnCmp::Equal,
+                    mod_revision,
+                ))
+                .and_then(PutRequest::new(key.clone(), next.to_string()).lease(lease))
+                .or_else(RangeRequest::new(KeyRange::key(key.clone())));
+
+            let resp = self.txn(req).await?;
+            if resp.succeeded {
+                return Ok(next);
+            }
+            current = resp.range_kvs().next().cloned();
+        }
+
+        Err(Error::TooManyConflicts(CAS_MAX_ATTEMPTS))
+    }
+
+    /// Gets the value of the key, or `default` if the key does not exist. The key is not created.
+    pub async fn get_or<K>(&self, key: K, default: Vec<u8>) -> Result<Vec<u8>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let kv = self.get(KeyRange::key(key)).await?.kvs.pop();
+
+        Ok(kv.map_or(default, |kv| kv.value.into()))
+    }
+
+    /// Gets the key-value of a typed key, or `None` if the key does not exist.
+    pub async fn get_typed<K>(&self, key: &K) -> Result<Option<KeyValue>>
+    where
+        K: Key + ?Sized,
+    {
+        Ok(self.get(KeyRange::key(key.to_key())).await?.kvs.pop())
+    }
+
+    /// Puts the value under a typed key.
+    pub async fn put_typed<K, V>(&self, key: &K, value: V) -> Result<PutResponse>
+    where
+        K: Key + ?Sized,
+        V: Into<Vec<u8>>,
+    {
+        self.put(PutRequest::new(key.to_key(), value)).await
+    }
+
+    /// Fetches the given keys in a single transaction, and returns their key-values in the same
+    /// order, with `None` for the keys that do not exist.
+    pub async fn get_multi(&self, keys: Vec<Vec<u8>>) -> Result<Vec<Option<KeyValue>>> {
+        let req = keys.into_iter().fold(TxnRequest::new(), |req, key| {
+            req.and_then(RangeRequest::new(KeyRange::key(key)))
+        });
+
+        let resp = self.txn(req).await?;
+
+        Ok(resp
+            .responses
+            .into_iter()
+            .map(|resp| match resp {
+                TxnOpResponse::Range(range) => range.kvs.into_iter().next(),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Waits until the value of the key satisfies the predicate, and returns the matching key-value.
+    ///
+    /// The key is read first, and only watched if its current value does not match. A deleted key
+    /// never matches.
+    ///
+    /// # Errors
+    /// Will returns `Err(Error::Timeout)` if no matching value is seen before the timeout elapses,
+    /// and the error which ended the watch if it ended before, see `WatchEvents::error`.
+    pub async fn wait_for<K, P>(&self, key: K, predicate: P, timeout: Duration) -> Result<KeyValue>
+    where
+        K: Into<Vec<u8>>,
+        P: Fn(&[u8]) -> bool,
+    {
+        let key_range = KeyRange::key(key);
+
+        let wait = async {
+            let resp = self.get(key_range.clone()).await?;
+            if let Some(kv) = resp.kvs.into_iter().find(|kv| predicate(&kv.value)) {
+                return Ok(kv);
+            }
+
+            let req =
+                WatchCreateRequest::create(key_range).start_revision(resp.header.revision() + 1);
+            let (stream, canceler) = self.watch(req).await?;
+            let mut events = WatchEvents::new(stream, canceler);
+
+            while let Some(event) = events.next().await {
+                if event.is_put() && predicate(&event.kv.value) {
+                    events.cancel().await?;
+                    return Ok(event.kv);
+                }
+            }
+
+            Err(events.take_error().unwrap_or(Error::WatchEventExhausted))
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    /// Waits until the key is deleted, e.g. to wait for the holder of an ephemeral key to release
+    /// it. Returns immediately if the key does not exist.
+    ///
+    /// # Errors
+    /// Will returns `Err(Error::Timeout)` if the key is not deleted before the timeout elapses,
+    /// and the error which ended the watch if it ended before, see `WatchEvents::error`.
+    pub async fn wait_for_delete<K>(&self, key: K, timeout: Duration) -> Result<()>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let key_range = KeyRange::key(key);
+
+        let wait = async {
+            let resp = self.get(key_range.clone()).await?;
+            if resp.kvs.is_empty() {
+                return Ok(());
+            }
+
+            let req = WatchCreateRequest::create(key_range)
+                .start_revision(resp.header.revision() + 1)
+                .no_put();
+            let (stream, canceler) = self.watch(req).await?;
+            let mut events = WatchEvents::new(stream, canceler);
+
+            while let Some(event) = events.next().await {
+                if event.is_delete() {
+                    events.cancel().await?;
+                    return Ok(());
+                }
+            }
+
+            Err(events.take_error().unwrap_or(Error::WatchEventExhausted))
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    /// Gets the range once the member serving the request has applied at least `min_revision`,
+    /// e.g. a revision handed over by another process after a write.
+    ///
+    /// Linearizable reads, the default, always see every applied revision, so this is mostly
+    /// useful with serializable reads, which may be served by a lagging member. The request is
+    /// retried until the member catches up.
+    ///
+    /// # Errors
+    /// Will return `Err(Error::Timeout)` if the member does not catch up in time.
+    pub async fn get_after(
+        &self,
+        req: RangeRequest,
+        min_revision: i64,
+        timeout: Duration,
+    ) -> Result<RangeResponse> {
+        let wait = async {
+            loop {
+                let resp = self.get(req.clone()).await?;
+                if resp.header.revision() >= min_revision {
+                    return Ok(resp);
+                }
+
+                tokio::time::sleep(GET_AFTER_RETRY_INTERVAL).await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    /// Creates a watch which is canceled once the token is cancelled.
+    ///
+    /// On cancellation a cancel request is sent to the server, and the returned stream ends after
+    /// the events already in flight have been delivered.
+    pub async fn watch_with_cancel<R>(
+        &self,
+        req: R,
+        token: CancellationToken,
+    ) -> Result<WatchStream>
+    where
+        R: Into<WatchCreateRequest> + Send,
+    {
+        let (stream, canceler) = self.watch(req).await?;
+
+        tokio::spawn(async move {
+            let cancelled = {
+                let cancelled = std::pin::pin!(token.cancelled());
+                let closed = std::pin::pin!(canceler.closed());
+                matches!(select(cancelled, closed).await, Either::Left(_))
+            };
+
+            // The stream may have been dropped in the meantime, in which case there is nothing
+            // left to cancel.
+            if cancelled {
+                let _ = canceler.cancel().await;
+            }
+        });
+
+        Ok(stream)
+    }
+
+    /// Campaigns for the leadership of the election for at most `timeout`.
+    ///
+    /// Returns `Ok(None)` if the candidate was not elected in time, in which case the pending
+    /// campaign is resigned.
+    pub async fn campaign_timeout<N, V>(
+        &self,
+        name: N,
+        lease: LeaseId,
+        value: V,
+        timeout: Duration,
+    ) -> Result<Option<CampaignResponse>>
+    where
+        N: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let req = CampaignRequest::new(name, lease, value);
+        self.campaign_until(req, tokio::time::sleep(timeout)).await
+    }
+
+    /// Campaigns for the leadership of the election until the token is cancelled.
+    ///
+    /// Returns `Ok(None)` if the candidate was not elected before the cancellation, in which case
+    /// the pending campaign is resigned.
+    pub async fn campaign_with_cancel<N, V>(
+        &self,
+        name: N,
+        lease: LeaseId,
+        value: V,
+        token: CancellationToken,
+    ) -> Result<Option<CampaignResponse>>
+    where
+        N: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let req = CampaignRequest::new(name, lease, value);
+        self.campaign_until(req, token.cancelled()).await
+    }
+
+    async fn campaign_until<F>(
+        &self,
+        req: CampaignRequest,
+        stop: F,
+    ) -> Result<Option<CampaignResponse>>
+    where
+        F: Future<Output = ()>,
+    {
+        let candidate_key = req.candidate_key();
+
+        // The campaign is dropped at the end of the block, which cancels the pending request.
+        let elected = {
+            let campaign = std::pin::pin!(self.campaign(req));
+            let stop = std::pin::pin!(stop);
+            match select(campaign, stop).await {
+                Either::Left((resp, _)) => Some(resp?),
+                Either::Right(_) => None,
+            }
+        };
+
+        if elected.is_none() {
+            // The candidate key may already have been created, or even elected right before the
+            // deadline, so it is deleted like a resign would.
+            self.delete(KeyRange::key(candidate_key)).await?;
+        }
+
+        Ok(elected)
+    }
+
+    /// Evaluates the compares in a transaction without executing any operation,
+    /// and returns whether all of them hold.
+    pub async fn txn_check<I>(&self, compares: I) -> Result<bool>
+    where
+        I: IntoIterator<Item = TxnCompare>,
+    {
+        Ok(self
+            .txn(TxnRequest::new().when_all(compares))
+            .await?
+            .succeeded)
+    }
+
+    /// Starts a background task compacting the key-value store every `interval`,
+    /// keeping the history described by `retention`.
+    ///
+    /// The task runs until the returned handle is stopped. Failed compactions are retried on the
+    /// next tick.
+    pub fn auto_compact(&self, retention: CompactRetention, interval: Duration) -> AutoCompactor {
+        let cli = self.clone();
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut samples = VecDeque::new();
+            let mut compacted = 0;
+
+            loop {
+                ticker.tick().await;
+
+                let revision = match cli.current_revision().await {
+                    Ok(revision) => revision,
+                    Err(_) => continue,
+                };
+
+                let target = match retention {
+                    CompactRetention::Revisions(n) => revision - n,
+                    CompactRetention::Period(period) => {
+                        let now = Instant::now();
+                        samples.push_back((now, revision));
+
+                        let mut target = 0;
+                        while let Some((at, rev)) = samples.front() {
+                            if now.duration_since(*at) < period {
+                                break;
+                            }
+                            target = *rev;
+                            samples.pop_front();
+                        }
+                        target
+                    }
+                };
+
+                if target > compacted && cli.compact(target).await.is_ok() {
+                    compacted = target;
+                }
+            }
+        });
+
+        AutoCompactor::new(task)
+    }
+
+    /// Counts the keys deleted, and compacts in the background once the threshold configured
+    /// with `ClientConfig::compact_after_deletes` is reached.
+    fn count_deletes(&self, resp: &DeleteResponse) {
+        let Some(compaction) = self.cfg.compact_after_deletes else {
+            return;
+        };
+
+        let deleted = resp.deleted;
+        let total = self
+            .deletes_since_compaction
+            .fetch_add(deleted, Ordering::Relaxed)
+            + deleted;
+        if total < compaction.deleted_keys {
+            return;
+        }
+
+        // Only the delete which crosses the threshold compacts.
+        if self
+            .deletes_since_compaction
+            .compare_exchange(total, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        let target = resp.header.revision() - compaction.keep_revisions;
+        if target <= 0 {
+            return;
+        }
+
+        let cli = self.clone();
+        tokio::spawn(async move {
+            if cli.compact(target).await.is_err() {
+                cli.deletes_since_compaction
+                    .fetch_add(total, Ordering::Relaxed);
+            }
+        });
+    }
+}
+
+impl KeyValueOp for Client {
+    async fn put<R>(&self, req: R) -> Result<PutResponse>
+    where
+        R: Into<PutRequest>,
+    {
+        let req = req.into();
+        if req.is_with_current() {
+            let key = req.key().to_vec();
+            let txn = TxnRequest::new()
+                .and_then(req)
+                .and_then(RangeRequest::new(KeyRange::key(key)));
+
+            let resp = self.txn(txn).await?;
+            let mut responses = resp.responses.into_iter();
+            return match (responses.next(), responses.next()) {
+                (Some(TxnOpResponse::Put(mut put)), Some(TxnOpResponse::Range(range))) => {
+                    put.header = resp.header;
+                    put.current = range.kvs.into_iter().next();
+                    Ok(put)
+                }
+                _ => Err(Error::ExecuteFailed { last_status: None }),
+            };
+        }
+
+        let req: etcdserverpb::PutRequest = req.into();
+        self.check_request_size(&req)?;
+
+        let req = tonic::Request::new(req);
+        let resp = self
+            .execute_write(req, false, |mut kv_client, req| async move {
+                kv_client.put(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn get<R>(&self, req: R) -> Result<RangeResponse>
+    where
+        R: Into<RangeRequest>,
+    {
+        let req: etcdserverpb::RangeRequest = req.into().into();
+        let kv_client = match &self.read_kv_client {
+            Some(read_kv_client) if req.serializable => read_kv_client,
+            _ => &self.kv_client,
+        };
+
+        let requested = req.revision;
+        let req = tonic::Request::new(req);
+        let resp = match self
+            .execute_with_retries(req, |req| async { kv_client.clone().range(req).await })
+            .await
+        {
+            Ok(resp) => resp,
+            Err(e)
+                if e.status().is_some_and(|s| {
+                    s.message()
+                        .contains("required revision is a future revision")
+                }) =>
+            {
+                // Not through `current_revision`, as async functions cannot recurse.
+                let req =
+                    tonic::Request::new(RangeRequest::new(KeyRange::key([0])).count_only().into());
+                let resp: RangeResponse = self
+                    .execute_with_retries(req, |req| async {
+                        self.kv_client.clone().range(req).await
+                    })
+                    .await?
+                    .into_inner()
+                    .try_into()?;
+
+                return Err(Error::FutureRevision {
+                    requested,
+                    current: resp.header.revision(),
+                });
+            }
+            Err(e) => return Err(e),
+        };
+
+        resp.into_inner().try_into()
+    }
+
+    async fn get_all(&self) -> Result<RangeResponse> {
+        self.get(KeyRange::all()).await
+    }
+
+    async fn get_by_prefix<K>(&self, p: K) -> Result<RangeResponse>
+    where
+        K: Into<Vec<u8>>,
+    {
+        self.get(KeyRange::prefix(p)).await
+    }
+
+    async fn get_range<F, E>(&self, from: F, end: E) -> Result<RangeResponse>
+    where
+        F: Into<Vec<u8>>,
+        E: Into<Vec<u8>>,
     {
         self.get(KeyRange::range(from, end)).await
     }
@@ -423,12 +2707,15 @@ impl KeyValueOp for Client {
     {
         let req = tonic::Request::new(req.into().into());
         let resp = self
-            .execute_with_retries(req, |req| async {
-                self.kv_client.clone().delete_range(req).await
+            .execute_write(req, false, |mut kv_client, req| async move {
+                kv_client.delete_range(req).await
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        let resp: DeleteResponse = resp.into_inner().try_into()?;
+        self.count_deletes(&resp);
+
+        Ok(resp)
     }
 
     async fn delete_all(&self) -> Result<DeleteResponse> {
@@ -454,12 +2741,25 @@ impl KeyValueOp for Client {
     where
         R: Into<TxnRequest>,
     {
-        let req = tonic::Request::new(req.into().into());
-        let resp = self
-            .execute_with_retries(req, |req| async { self.kv_client.clone().txn(req).await })
-            .await?;
+        let req = req.into();
+        let read_only = req.is_read_only();
+        let serializable_read = req.is_serializable_read();
+        let req: etcdserverpb::TxnRequest = req.into();
+        self.check_request_size(&req)?;
+
+        let req = tonic::Request::new(req);
+        let resp = if serializable_read {
+            let kv_client = self.read_kv_client.as_ref().unwrap_or(&self.kv_client);
+            self.execute_with_retries(req, |req| async { kv_client.clone().txn(req).await })
+                .await?
+        } else {
+            self.execute_write(req, read_only, |mut kv_client, req| async move {
+                kv_client.txn(req).await
+            })
+            .await?
+        };
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn compact<R>(&self, req: R) -> Result<CompactResponse>
@@ -468,12 +2768,12 @@ impl KeyValueOp for Client {
     {
         let req = tonic::Request::new(req.into().into());
         let resp = self
-            .execute_with_retries(req, |req| async {
-                self.kv_client.clone().compact(req).await
+            .execute_write(req, false, |mut kv_client, req| async move {
+                kv_client.compact(req).await
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 }
 
@@ -482,39 +2782,10 @@ impl WatchOp for Client {
     where
         R: Into<WatchCreateRequest>,
     {
-        let (tx, rx) = channel::<etcdserverpb::WatchRequest>(128);
-
-        tx.send(req.into().into()).await?;
+        let (stream, mut cancelers) = self.open_watches(vec![req.into()]).await?;
+        let canceler = cancelers.pop().ok_or(Error::CreateWatch)?;
 
-        let mut req = tonic::Request::new(ReceiverStream::new(rx));
-        self.refresh_token().await?;
-        self.set_token(&mut req).await;
-
-        req.metadata_mut()
-            .insert("hasleader", "true".try_into().unwrap());
-
-        let resp = self.watch_client.clone().watch(req).await?;
-
-        let mut inbound = resp.into_inner();
-
-        let watch_id = match inbound.message().await? {
-            Some(resp) => {
-                if !resp.created {
-                    return Err(Error::WatchEvent(
-                        "should receive created event at first".to_owned(),
-                    ));
-                }
-                if resp.canceled {
-                    return Err(Error::WatchEvent(resp.cancel_reason));
-                }
-                assert!(resp.events.is_empty(), "received created event {:?}", resp);
-                resp.watch_id
-            }
-
-            None => return Err(Error::CreateWatch),
-        };
-
-        Ok((WatchStream::new(inbound), WatchCanceler::new(watch_id, tx)))
+        Ok((stream, canceler))
     }
 }
 
@@ -530,7 +2801,7 @@ impl LeaseOp for Client {
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn revoke<R>(&self, req: R) -> Result<LeaseRevokeResponse>
@@ -544,7 +2815,7 @@ impl LeaseOp for Client {
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn keep_alive_for(&self, lease_id: LeaseId) -> Result<LeaseKeepAlive> {
@@ -573,7 +2844,37 @@ impl LeaseOp for Client {
             }
         };
 
-        Ok(LeaseKeepAlive::new(lease_id, req_tx, resp_rx))
+        Ok(LeaseKeepAlive::new(lease_id, self.clone(), req_tx, resp_rx))
+    }
+
+    async fn keep_alive_many(&self, lease_ids: Vec<LeaseId>) -> Result<MultiKeepAlive> {
+        if lease_ids.is_empty() {
+            return Err(Error::KeepAliveLease);
+        }
+
+        // Every round sends one request per lease before reading the responses back.
+        let (req_tx, req_rx) = channel(lease_ids.len());
+
+        let req_rx = ReceiverStream::new(req_rx);
+
+        for id in lease_ids.iter() {
+            req_tx
+                .send(LeaseKeepAliveRequest { id: *id })
+                .await
+                .map_err(|_| Error::ChannelClosed)?;
+        }
+
+        let resp_rx = self
+            .lease_client
+            .clone()
+            .lease_keep_alive(req_rx)
+            .await?
+            .into_inner();
+
+        let mut keep_alive = MultiKeepAlive::new(lease_ids, self.clone(), req_tx, resp_rx);
+        keep_alive.receive().await?;
+
+        Ok(keep_alive)
     }
 
     async fn time_to_live<R>(&self, req: R) -> Result<LeaseTimeToLiveResponse>
@@ -587,7 +2888,21 @@ impl LeaseOp for Client {
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
+    }
+
+    async fn lease_leases(&self) -> Result<LeaseLeasesResponse> {
+        let req = tonic::Request::new(LeaseLeasesRequest::new().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.lease_client.clone().lease_leases(req).await
+            })
+            .await;
+
+        self.gate(resp, Feature::LeaseLeases)
+            .await?
+            .into_inner()
+            .try_into()
     }
 }
 
@@ -603,7 +2918,7 @@ impl ClusterOp for Client {
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn member_remove<R>(&self, req: R) -> Result<MemberRemoveResponse>
@@ -617,7 +2932,7 @@ impl ClusterOp for Client {
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn member_update<R>(&self, req: R) -> Result<MemberUpdateResponse>
@@ -631,7 +2946,7 @@ impl ClusterOp for Client {
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
     }
 
     async fn member_list(&self) -> Result<MemberListResponse> {
@@ -642,6 +2957,238 @@ impl ClusterOp for Client {
             })
             .await?;
 
-        Ok(resp.into_inner().into())
+        resp.into_inner().try_into()
+    }
+}
+
+impl MaintenanceOp for Client {
+    async fn status(&self) -> Result<StatusResponse> {
+        let req = tonic::Request::new(StatusRequest::default().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.maintenance_client.clone().status(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn hash_kv<R>(&self, req: R) -> Result<HashKvResponse>
+    where
+        R: Into<HashKvRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.maintenance_client.clone().hash_kv(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn downgrade<R>(&self, req: R) -> Result<DowngradeResponse>
+    where
+        R: Into<DowngradeRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.maintenance_client.clone().downgrade(req).await
+            })
+            .await;
+
+        self.gate(resp, Feature::Downgrade)
+            .await?
+            .into_inner()
+            .try_into()
+    }
+}
+
+impl ElectionOp for Client {
+    async fn campaign<R>(&self, req: R) -> Result<CampaignResponse>
+    where
+        R: Into<CampaignRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.election_client.clone().campaign(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn proclaim<R>(&self, req: R) -> Result<ProclaimResponse>
+    where
+        R: Into<ProclaimRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.election_client.clone().proclaim(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn leader<R>(&self, req: R) -> Result<LeaderResponse>
+    where
+        R: Into<LeaderRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.election_client.clone().leader(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn resign<R>(&self, req: R) -> Result<ResignResponse>
+    where
+        R: Into<ResignRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.election_client.clone().resign(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn proclaim_loop<F, V>(
+        &self,
+        leader: LeaderKey,
+        interval: Duration,
+        mut value_fn: F,
+    ) -> Result<()>
+    where
+        F: FnMut() -> V,
+        V: Into<Vec<u8>>,
+    {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match self
+                .proclaim(ProclaimRequest::new(leader.clone(), value_fn()))
+                .await
+            {
+                Ok(_) => {}
+                Err(e)
+                    if e.status()
+                        .is_some_and(|s| s.message().contains("election: not leader")) =>
+                {
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl LockOp for Client {
+    async fn lock<R>(&self, req: R) -> Result<LockResponse>
+    where
+        R: Into<LockRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.lock_client.clone().lock(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+
+    async fn unlock<R>(&self, req: R) -> Result<UnlockResponse>
+    where
+        R: Into<UnlockRequest>,
+    {
+        let req = tonic::Request::new(req.into().into());
+        let resp = self
+            .execute_with_retries(req, |req| async {
+                self.lock_client.clone().unlock(req).await
+            })
+            .await?;
+
+        resp.into_inner().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use super::*;
+
+    async fn lazy_client() -> Client {
+        // Channels connect on the first request, so no server is needed as long as none is sent.
+        let mut cfg = ClientConfig::new(vec![Endpoint::new("http://127.0.0.1:2379")]);
+        cfg.max_retries = 2;
+        Client::connect(cfg).await.unwrap()
+    }
+
+    fn leader_changed() -> Status {
+        Status::unavailable("etcdserver: leader changed")
+    }
+
+    #[tokio::test]
+    async fn read_is_retried_on_ambiguous_leader_change() {
+        let cli = lazy_client().await;
+        let attempts = AtomicU32::new(0);
+
+        let req = tonic::Request::new(etcdserverpb::RangeRequest::default());
+        let result: Result<()> = cli
+            .execute_with_retries(req, |_| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(leader_changed())
+            })
+            .await;
+
+        assert!(matches!(result, Err(Error::ExecuteFailed { .. })));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn write_is_not_retried_on_ambiguous_leader_change() {
+        let cli = lazy_client().await;
+        let attempts = AtomicU32::new(0);
+
+        let req = tonic::Request::new(etcdserverpb::PutRequest::default());
+        let result: Result<()> = cli
+            .execute_write(req, false, |_, _| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(leader_changed())
+            })
+            .await;
+
+        assert!(result.is_err_and(|e| e.is_leader_change()));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn read_only_txn_is_retried_on_ambiguous_leader_change() {
+        let cli = lazy_client().await;
+        let attempts = AtomicU32::new(0);
+
+        let req = tonic::Request::new(etcdserverpb::TxnRequest::default());
+        let result: Result<()> = cli
+            .execute_write(req, true, |_, _| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(leader_changed())
+            })
+            .await;
+
+        assert!(matches!(result, Err(Error::ExecuteFailed { .. })));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
 }
@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::{Member, ResponseHeader};
+use crate::{Error, Member, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct MemberAddRequest {
@@ -35,16 +35,58 @@ impl From<MemberAddRequest> for etcdserverpb::MemberAddRequest {
 #[derive(Debug, Clone)]
 pub struct MemberAddResponse {
     pub header: ResponseHeader,
+    /// The added member, with the ID assigned to it by the cluster.
     pub member: Member,
+    /// The list of all members after the new member was added, including it.
     pub members: Vec<Member>,
 }
 
-impl From<etcdserverpb::MemberAddResponse> for MemberAddResponse {
-    fn from(proto: etcdserverpb::MemberAddResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
-            member: From::from(proto.member.expect("must get a new member")),
+impl TryFrom<etcdserverpb::MemberAddResponse> for MemberAddResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::MemberAddResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            member: From::from(proto.member.ok_or(Error::MissingField("new member"))?),
             members: proto.members.into_iter().map(From::from).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: u64) -> etcdserverpb::Member {
+        etcdserverpb::Member {
+            id,
+            peer_ur_ls: vec![format!("http://10.0.0.{id}:2380")],
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn response_has_added_member_and_all_members() {
+        let resp = MemberAddResponse::try_from(etcdserverpb::MemberAddResponse {
+            header: Some(Default::default()),
+            member: Some(member(3)),
+            members: vec![member(1), member(2), member(3)],
+        })
+        .unwrap();
+
+        assert_eq!(resp.member.id, 3);
+        assert_eq!(resp.member.peer_urls, ["http://10.0.0.3:2380"]);
+        assert!(resp.members.iter().any(|m| m.id == resp.member.id));
+        assert_eq!(resp.members.len(), 3);
+    }
+
+    #[test]
+    fn response_without_added_member_is_an_error() {
+        let resp = MemberAddResponse::try_from(etcdserverpb::MemberAddResponse {
+            header: Some(Default::default()),
+            member: None,
+            members: vec![member(1)],
+        });
+        assert!(matches!(resp, Err(Error::MissingField(_))));
+    }
 }
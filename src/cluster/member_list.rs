@@ -1,5 +1,7 @@
+use std::collections::HashMap;
+
 use crate::proto::etcdserverpb;
-use crate::{Member, ResponseHeader};
+use crate::{Error, Member, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct MemberListRequest {
@@ -36,11 +38,33 @@ pub struct MemberListResponse {
     pub members: Vec<Member>,
 }
 
-impl From<etcdserverpb::MemberListResponse> for MemberListResponse {
-    fn from(proto: etcdserverpb::MemberListResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl MemberListResponse {
+    /// Returns the members keyed by their ID.
+    pub fn by_id(&self) -> HashMap<u64, Member> {
+        self.members
+            .iter()
+            .map(|member| (member.id, member.clone()))
+            .collect()
+    }
+
+    /// Returns the members keyed by their name. Members which are not started yet have no name,
+    /// and are left out.
+    pub fn by_name(&self) -> HashMap<String, Member> {
+        self.members
+            .iter()
+            .filter(|member| !member.name.is_empty())
+            .map(|member| (member.name.clone(), member.clone()))
+            .collect()
+    }
+}
+
+impl TryFrom<etcdserverpb::MemberListResponse> for MemberListResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::MemberListResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             members: proto.members.into_iter().map(From::from).collect(),
-        }
+        })
     }
 }
@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::{Member, ResponseHeader};
+use crate::{Error, Member, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct MemberRemoveRequest {
@@ -32,11 +32,13 @@ pub struct MemberRemoveResponse {
     pub members: Vec<Member>,
 }
 
-impl From<etcdserverpb::MemberRemoveResponse> for MemberRemoveResponse {
-    fn from(proto: etcdserverpb::MemberRemoveResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::MemberRemoveResponse> for MemberRemoveResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::MemberRemoveResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             members: proto.members.into_iter().map(From::from).collect(),
-        }
+        })
     }
 }
@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::{Member, ResponseHeader};
+use crate::{Error, Member, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct MemberUpdateRequest {
@@ -38,11 +38,13 @@ pub struct MemberUpdateResponse {
     pub members: Vec<Member>,
 }
 
-impl From<etcdserverpb::MemberUpdateResponse> for MemberUpdateResponse {
-    fn from(proto: etcdserverpb::MemberUpdateResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::MemberUpdateResponse> for MemberUpdateResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::MemberUpdateResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             members: proto.members.into_iter().map(From::from).collect(),
-        }
+        })
     }
 }
@@ -0,0 +1,47 @@
+//! DNS SRV discovery of the cluster members, following etcd's DNS discovery conventions.
+
+use hickory_resolver::TokioAsyncResolver;
+
+use crate::{Error, Result};
+
+/// Looks up the `_<service>._tcp.<domain>` SRV records and returns the URLs of the members.
+///
+/// Services ending with `-ssl`, like `etcd-client-ssl`, are served over https.
+pub(crate) async fn lookup_srv(service: &str, domain: &str) -> Result<Vec<String>> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .map_err(|e| Error::Discovery(e.to_string()))?;
+
+    let name = format!("_{}._tcp.{}", service, domain);
+    let lookup = resolver
+        .srv_lookup(name.as_str())
+        .await
+        .map_err(|e| Error::Discovery(e.to_string()))?;
+
+    let scheme = if service.ends_with("-ssl") {
+        "https"
+    } else {
+        "http"
+    };
+
+    let urls: Vec<String> = lookup
+        .iter()
+        .map(|srv| {
+            let target = srv.target().to_utf8();
+            format!(
+                "{}://{}:{}",
+                scheme,
+                target.trim_end_matches('.'),
+                srv.port()
+            )
+        })
+        .collect();
+
+    if urls.is_empty() {
+        return Err(Error::Discovery(format!(
+            "no SRV records found for {}",
+            name
+        )));
+    }
+
+    Ok(urls)
+}
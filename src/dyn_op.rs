@@ -0,0 +1,233 @@
+//! Object-safe counterparts of the operation traits.
+//!
+//! The `*Op` traits are generic over their request types, so they cannot be turned into trait
+//! objects. The `Dyn*Op` traits take concrete request types and return boxed futures instead, which
+//! allows storing e.g. a `Box<dyn DynKeyValueOp>` and swapping implementations at runtime.
+
+use futures::future::BoxFuture;
+
+use crate::{
+    AuthDisableResponse, AuthEnableResponse, AuthOp, AuthRoleAddRequest, AuthRoleAddResponse,
+    AuthRoleDeleteRequest, AuthRoleDeleteResponse, AuthRoleGrantPermissionRequest,
+    AuthRoleGrantPermissionResponse, AuthRoleListResponse, AuthStatusResponse, AuthenticateRequest,
+    AuthenticateResponse, Client, ClusterOp, CompactRequest, CompactResponse, DeleteRequest,
+    DeleteResponse, KeyValueOp, LeaseGrantRequest, LeaseGrantResponse, LeaseId, LeaseKeepAlive,
+    LeaseOp, LeaseRevokeRequest, LeaseRevokeResponse, LeaseTimeToLiveRequest,
+    LeaseTimeToLiveResponse, MaintenanceOp, MemberAddRequest, MemberAddResponse,
+    MemberListResponse, MemberRemoveRequest, MemberRemoveResponse, MemberUpdateRequest,
+    MemberUpdateResponse, PutRequest, PutResponse, RangeRequest, RangeResponse, Result,
+    StatusResponse, TxnRequest, TxnResponse, WatchCanceler, WatchCreateRequest, WatchOp,
+    WatchStream,
+};
+
+/// Object-safe counterpart of [`KeyValueOp`].
+pub trait DynKeyValueOp: Send + Sync {
+    fn put(&self, req: PutRequest) -> BoxFuture<'_, Result<PutResponse>>;
+    fn get(&self, req: RangeRequest) -> BoxFuture<'_, Result<RangeResponse>>;
+    fn delete(&self, req: DeleteRequest) -> BoxFuture<'_, Result<DeleteResponse>>;
+    fn txn(&self, req: TxnRequest) -> BoxFuture<'_, Result<TxnResponse>>;
+    fn compact(&self, req: CompactRequest) -> BoxFuture<'_, Result<CompactResponse>>;
+}
+
+/// Object-safe counterpart of [`LeaseOp`].
+pub trait DynLeaseOp: Send + Sync {
+    fn grant_lease(&self, req: LeaseGrantRequest) -> BoxFuture<'_, Result<LeaseGrantResponse>>;
+    fn revoke(&self, req: LeaseRevokeRequest) -> BoxFuture<'_, Result<LeaseRevokeResponse>>;
+    fn keep_alive_for(&self, lease_id: LeaseId) -> BoxFuture<'_, Result<LeaseKeepAlive>>;
+    fn time_to_live(
+        &self,
+        req: LeaseTimeToLiveRequest,
+    ) -> BoxFuture<'_, Result<LeaseTimeToLiveResponse>>;
+}
+
+/// Object-safe counterpart of [`WatchOp`].
+pub trait DynWatchOp: Send + Sync {
+    fn watch(&self, req: WatchCreateRequest)
+        -> BoxFuture<'_, Result<(WatchStream, WatchCanceler)>>;
+}
+
+/// Object-safe counterpart of [`ClusterOp`].
+pub trait DynClusterOp: Send + Sync {
+    fn member_add(&self, req: MemberAddRequest) -> BoxFuture<'_, Result<MemberAddResponse>>;
+    fn member_remove(
+        &self,
+        req: MemberRemoveRequest,
+    ) -> BoxFuture<'_, Result<MemberRemoveResponse>>;
+    fn member_update(
+        &self,
+        req: MemberUpdateRequest,
+    ) -> BoxFuture<'_, Result<MemberUpdateResponse>>;
+    fn member_list(&self) -> BoxFuture<'_, Result<MemberListResponse>>;
+}
+
+/// Object-safe counterpart of [`AuthOp`].
+pub trait DynAuthOp: Send + Sync {
+    fn authenticate(&self, req: AuthenticateRequest)
+        -> BoxFuture<'_, Result<AuthenticateResponse>>;
+    fn auth_status(&self) -> BoxFuture<'_, Result<AuthStatusResponse>>;
+    fn auth_enable(&self) -> BoxFuture<'_, Result<AuthEnableResponse>>;
+    fn auth_disable(&self) -> BoxFuture<'_, Result<AuthDisableResponse>>;
+    fn role_add(&self, req: AuthRoleAddRequest) -> BoxFuture<'_, Result<AuthRoleAddResponse>>;
+    fn role_delete(
+        &self,
+        req: AuthRoleDeleteRequest,
+    ) -> BoxFuture<'_, Result<AuthRoleDeleteResponse>>;
+    fn role_list(&self) -> BoxFuture<'_, Result<AuthRoleListResponse>>;
+    fn role_grant_permission(
+        &self,
+        req: AuthRoleGrantPermissionRequest,
+    ) -> BoxFuture<'_, Result<AuthRoleGrantPermissionResponse>>;
+}
+
+/// Object-safe counterpart of [`MaintenanceOp`].
+pub trait DynMaintenanceOp: Send + Sync {
+    fn status(&self) -> BoxFuture<'_, Result<StatusResponse>>;
+}
+
+impl DynKeyValueOp for Client {
+    fn put(&self, req: PutRequest) -> BoxFuture<'_, Result<PutResponse>> {
+        Box::pin(KeyValueOp::put(self, req))
+    }
+
+    fn get(&self, req: RangeRequest) -> BoxFuture<'_, Result<RangeResponse>> {
+        Box::pin(KeyValueOp::get(self, req))
+    }
+
+    fn delete(&self, req: DeleteRequest) -> BoxFuture<'_, Result<DeleteResponse>> {
+        Box::pin(KeyValueOp::delete(self, req))
+    }
+
+    fn txn(&self, req: TxnRequest) -> BoxFuture<'_, Result<TxnResponse>> {
+        Box::pin(KeyValueOp::txn(self, req))
+    }
+
+    fn compact(&self, req: CompactRequest) -> BoxFuture<'_, Result<CompactResponse>> {
+        Box::pin(KeyValueOp::compact(self, req))
+    }
+}
+
+#[cfg(feature = "mock")]
+impl DynKeyValueOp for crate::InMemoryKv {
+    fn put(&self, req: PutRequest) -> BoxFuture<'_, Result<PutResponse>> {
+        Box::pin(KeyValueOp::put(self, req))
+    }
+
+    fn get(&self, req: RangeRequest) -> BoxFuture<'_, Result<RangeResponse>> {
+        Box::pin(KeyValueOp::get(self, req))
+    }
+
+    fn delete(&self, req: DeleteRequest) -> BoxFuture<'_, Result<DeleteResponse>> {
+        Box::pin(KeyValueOp::delete(self, req))
+    }
+
+    fn txn(&self, req: TxnRequest) -> BoxFuture<'_, Result<TxnResponse>> {
+        Box::pin(KeyValueOp::txn(self, req))
+    }
+
+    fn compact(&self, req: CompactRequest) -> BoxFuture<'_, Result<CompactResponse>> {
+        Box::pin(KeyValueOp::compact(self, req))
+    }
+}
+
+impl DynLeaseOp for Client {
+    fn grant_lease(&self, req: LeaseGrantRequest) -> BoxFuture<'_, Result<LeaseGrantResponse>> {
+        Box::pin(LeaseOp::grant_lease(self, req))
+    }
+
+    fn revoke(&self, req: LeaseRevokeRequest) -> BoxFuture<'_, Result<LeaseRevokeResponse>> {
+        Box::pin(LeaseOp::revoke(self, req))
+    }
+
+    fn keep_alive_for(&self, lease_id: LeaseId) -> BoxFuture<'_, Result<LeaseKeepAlive>> {
+        Box::pin(LeaseOp::keep_alive_for(self, lease_id))
+    }
+
+    fn time_to_live(
+        &self,
+        req: LeaseTimeToLiveRequest,
+    ) -> BoxFuture<'_, Result<LeaseTimeToLiveResponse>> {
+        Box::pin(LeaseOp::time_to_live(self, req))
+    }
+}
+
+impl DynWatchOp for Client {
+    fn watch(
+        &self,
+        req: WatchCreateRequest,
+    ) -> BoxFuture<'_, Result<(WatchStream, WatchCanceler)>> {
+        Box::pin(WatchOp::watch(self, req))
+    }
+}
+
+impl DynClusterOp for Client {
+    fn member_add(&self, req: MemberAddRequest) -> BoxFuture<'_, Result<MemberAddResponse>> {
+        Box::pin(ClusterOp::member_add(self, req))
+    }
+
+    fn member_remove(
+        &self,
+        req: MemberRemoveRequest,
+    ) -> BoxFuture<'_, Result<MemberRemoveResponse>> {
+        Box::pin(ClusterOp::member_remove(self, req))
+    }
+
+    fn member_update(
+        &self,
+        req: MemberUpdateRequest,
+    ) -> BoxFuture<'_, Result<MemberUpdateResponse>> {
+        Box::pin(ClusterOp::member_update(self, req))
+    }
+
+    fn member_list(&self) -> BoxFuture<'_, Result<MemberListResponse>> {
+        Box::pin(ClusterOp::member_list(self))
+    }
+}
+
+impl DynAuthOp for Client {
+    fn authenticate(
+        &self,
+        req: AuthenticateRequest,
+    ) -> BoxFuture<'_, Result<AuthenticateResponse>> {
+        Box::pin(AuthOp::authenticate(self, req))
+    }
+
+    fn auth_status(&self) -> BoxFuture<'_, Result<AuthStatusResponse>> {
+        Box::pin(AuthOp::auth_status(self))
+    }
+
+    fn auth_enable(&self) -> BoxFuture<'_, Result<AuthEnableResponse>> {
+        Box::pin(AuthOp::auth_enable(self))
+    }
+
+    fn auth_disable(&self) -> BoxFuture<'_, Result<AuthDisableResponse>> {
+        Box::pin(AuthOp::auth_disable(self))
+    }
+
+    fn role_add(&self, req: AuthRoleAddRequest) -> BoxFuture<'_, Result<AuthRoleAddResponse>> {
+        Box::pin(AuthOp::role_add(self, req))
+    }
+
+    fn role_delete(
+        &self,
+        req: AuthRoleDeleteRequest,
+    ) -> BoxFuture<'_, Result<AuthRoleDeleteResponse>> {
+        Box::pin(AuthOp::role_delete(self, req))
+    }
+
+    fn role_list(&self) -> BoxFuture<'_, Result<AuthRoleListResponse>> {
+        Box::pin(AuthOp::role_list(self))
+    }
+
+    fn role_grant_permission(
+        &self,
+        req: AuthRoleGrantPermissionRequest,
+    ) -> BoxFuture<'_, Result<AuthRoleGrantPermissionResponse>> {
+        Box::pin(AuthOp::role_grant_permission(self, req))
+    }
+}
+
+impl DynMaintenanceOp for Client {
+    fn status(&self) -> BoxFuture<'_, Result<StatusResponse>> {
+        Box::pin(MaintenanceOp::status(self))
+    }
+}
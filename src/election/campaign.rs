@@ -0,0 +1,56 @@
+use super::LeaderKey;
+use crate::proto::v3electionpb;
+use crate::{Error, LeaseId, ResponseHeader, Result};
+
+#[derive(Debug, Clone)]
+pub struct CampaignRequest {
+    proto: v3electionpb::CampaignRequest,
+}
+
+impl CampaignRequest {
+    /// Creates a new CampaignRequest for the election, with the value to publish once elected.
+    /// The leadership is attached to the given lease.
+    pub fn new<N, V>(name: N, lease: LeaseId, value: V) -> Self
+    where
+        N: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        Self {
+            proto: v3electionpb::CampaignRequest {
+                name: name.into(),
+                lease,
+                value: value.into(),
+            },
+        }
+    }
+
+    /// Returns the key the candidate campaigns with, `<name>/<lease in hex>` as etcd derives it.
+    pub(crate) fn candidate_key(&self) -> Vec<u8> {
+        let mut key = self.proto.name.clone();
+        key.extend(format!("/{:x}", self.proto.lease).into_bytes());
+        key
+    }
+}
+
+impl From<CampaignRequest> for v3electionpb::CampaignRequest {
+    fn from(req: CampaignRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CampaignResponse {
+    pub header: ResponseHeader,
+    pub leader: LeaderKey,
+}
+
+impl TryFrom<v3electionpb::CampaignResponse> for CampaignResponse {
+    type Error = Error;
+
+    fn try_from(proto: v3electionpb::CampaignResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            leader: From::from(proto.leader.ok_or(Error::MissingField("leader"))?),
+        })
+    }
+}
@@ -0,0 +1,52 @@
+use crate::proto::v3electionpb;
+use crate::{Error, KeyValue, ResponseHeader, Result};
+
+#[derive(Debug, Clone)]
+pub struct LeaderRequest {
+    proto: v3electionpb::LeaderRequest,
+}
+
+impl LeaderRequest {
+    /// Creates a new LeaderRequest for the election.
+    pub fn new<N>(name: N) -> Self
+    where
+        N: Into<Vec<u8>>,
+    {
+        Self {
+            proto: v3electionpb::LeaderRequest { name: name.into() },
+        }
+    }
+}
+
+impl<N> From<N> for LeaderRequest
+where
+    N: Into<Vec<u8>>,
+{
+    fn from(name: N) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<LeaderRequest> for v3electionpb::LeaderRequest {
+    fn from(req: LeaderRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeaderResponse {
+    pub header: ResponseHeader,
+    /// The key-value holding the leadership and the value published by the leader.
+    pub kv: Option<KeyValue>,
+}
+
+impl TryFrom<v3electionpb::LeaderResponse> for LeaderResponse {
+    type Error = Error;
+
+    fn try_from(proto: v3electionpb::LeaderResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            kv: proto.kv.map(From::from),
+        })
+    }
+}
@@ -0,0 +1,89 @@
+//! The Election API elects a leader among the clients campaigning under the same election name.
+//!
+//! Campaigning attaches the candidate to a lease, so that leadership is lost once the lease expires.
+
+mod campaign;
+mod leader;
+mod proclaim;
+mod resign;
+
+pub use campaign::{CampaignRequest, CampaignResponse};
+pub use leader::{LeaderRequest, LeaderResponse};
+pub use proclaim::{ProclaimRequest, ProclaimResponse};
+pub use resign::{ResignRequest, ResignResponse};
+
+use std::{future::Future, time::Duration};
+
+use crate::proto::v3electionpb;
+use crate::{LeaseId, Result};
+
+pub trait ElectionOp {
+    /// Waits until the candidate is elected leader of the election.
+    fn campaign<R>(&self, req: R) -> impl Future<Output = Result<CampaignResponse>>
+    where
+        R: Into<CampaignRequest> + Send;
+
+    /// Updates the value published by the leader without another election.
+    fn proclaim<R>(&self, req: R) -> impl Future<Output = Result<ProclaimResponse>>
+    where
+        R: Into<ProclaimRequest> + Send;
+
+    /// Returns the current leader of the election.
+    fn leader<R>(&self, req: R) -> impl Future<Output = Result<LeaderResponse>>
+    where
+        R: Into<LeaderRequest> + Send;
+
+    /// Releases the leadership, so that another candidate can be elected.
+    fn resign<R>(&self, req: R) -> impl Future<Output = Result<ResignResponse>>
+    where
+        R: Into<ResignRequest> + Send;
+
+    /// Proclaims a fresh value from `value_fn` every `interval`, starting immediately.
+    ///
+    /// Completes with `Ok(())` once the leadership is lost, either because it was resigned or
+    /// because its lease expired.
+    fn proclaim_loop<F, V>(
+        &self,
+        leader: LeaderKey,
+        interval: Duration,
+        value_fn: F,
+    ) -> impl Future<Output = Result<()>>
+    where
+        F: FnMut() -> V + Send,
+        V: Into<Vec<u8>>;
+}
+
+/// LeaderKey identifies the ownership of an election.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderKey {
+    /// The name of the election.
+    pub name: Vec<u8>,
+    /// The key holding the leadership, which exists as long as the leader holds it.
+    pub key: Vec<u8>,
+    /// The creation revision of the key, used to detect the ownership of the leadership.
+    pub rev: i64,
+    /// The lease the leadership is attached to.
+    pub lease: LeaseId,
+}
+
+impl From<v3electionpb::LeaderKey> for LeaderKey {
+    fn from(proto: v3electionpb::LeaderKey) -> Self {
+        Self {
+            name: proto.name,
+            key: proto.key,
+            rev: proto.rev,
+            lease: proto.lease,
+        }
+    }
+}
+
+impl From<LeaderKey> for v3electionpb::LeaderKey {
+    fn from(value: LeaderKey) -> Self {
+        Self {
+            name: value.name,
+            key: value.key,
+            rev: value.rev,
+            lease: value.lease,
+        }
+    }
+}
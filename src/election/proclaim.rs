@@ -0,0 +1,44 @@
+use super::LeaderKey;
+use crate::proto::v3electionpb;
+use crate::{Error, ResponseHeader, Result};
+
+#[derive(Debug, Clone)]
+pub struct ProclaimRequest {
+    proto: v3electionpb::ProclaimRequest,
+}
+
+impl ProclaimRequest {
+    /// Creates a new ProclaimRequest publishing the value on behalf of the leader.
+    pub fn new<V>(leader: LeaderKey, value: V) -> Self
+    where
+        V: Into<Vec<u8>>,
+    {
+        Self {
+            proto: v3electionpb::ProclaimRequest {
+                leader: Some(leader.into()),
+                value: value.into(),
+            },
+        }
+    }
+}
+
+impl From<ProclaimRequest> for v3electionpb::ProclaimRequest {
+    fn from(req: ProclaimRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProclaimResponse {
+    pub header: ResponseHeader,
+}
+
+impl TryFrom<v3electionpb::ProclaimResponse> for ProclaimResponse {
+    type Error = Error;
+
+    fn try_from(proto: v3electionpb::ProclaimResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
+    }
+}
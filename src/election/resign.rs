@@ -0,0 +1,46 @@
+use super::LeaderKey;
+use crate::proto::v3electionpb;
+use crate::{Error, ResponseHeader, Result};
+
+#[derive(Debug, Clone)]
+pub struct ResignRequest {
+    proto: v3electionpb::ResignRequest,
+}
+
+impl ResignRequest {
+    /// Creates a new ResignRequest releasing the leadership.
+    pub fn new(leader: LeaderKey) -> Self {
+        Self {
+            proto: v3electionpb::ResignRequest {
+                leader: Some(leader.into()),
+            },
+        }
+    }
+}
+
+impl From<LeaderKey> for ResignRequest {
+    fn from(leader: LeaderKey) -> Self {
+        Self::new(leader)
+    }
+}
+
+impl From<ResignRequest> for v3electionpb::ResignRequest {
+    fn from(req: ResignRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResignResponse {
+    pub header: ResponseHeader,
+}
+
+impl TryFrom<v3electionpb::ResignResponse> for ResignResponse {
+    type Error = Error;
+
+    fn try_from(proto: v3electionpb::ResignResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
+    }
+}
@@ -1,15 +1,30 @@
 use crate::proto::etcdserverpb;
+use crate::{CancelReason, Feature};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("io error: {0}")]
     IOError(#[from] std::io::Error),
+    #[error("invalid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
     #[error("invalid URI: {0}")]
     InvalidURI(#[from] http::uri::InvalidUri),
     #[error("gRPC transport error: {0}")]
     Transport(#[from] tonic::transport::Error),
     #[error("response failed, status: {0}")]
-    Response(#[from] tonic::Status),
+    Response(Box<tonic::Status>),
+    #[error("not found: {0}")]
+    NotFound(Box<tonic::Status>),
+    #[error("permission denied: {0}")]
+    PermissionDenied(Box<tonic::Status>),
+    #[error("unauthenticated: {0}")]
+    Unauthenticated(Box<tonic::Status>),
+    #[error("deadline exceeded: {0}")]
+    DeadlineExceeded(Box<tonic::Status>),
+    #[error("resource exhausted: {0}")]
+    ResourceExhausted(Box<tonic::Status>),
+    #[error("authentication is not enabled: {0}")]
+    AuthNotEnabled(Box<tonic::Status>),
     #[error("channel closed")]
     ChannelClosed,
     #[error("failed to create watch")]
@@ -22,12 +37,208 @@ pub enum Error {
     WatchChannelSend(#[from] tokio::sync::mpsc::error::SendError<etcdserverpb::WatchRequest>),
     #[error("watch event exhausted")]
     WatchEventExhausted,
+    #[error("failed to authenticate as user {username:?}: {source}")]
+    AuthFailed {
+        username: String,
+        #[source]
+        source: Box<Error>,
+    },
     #[error("invalid metadata token: {0}")]
     InvalidMetadataToken(String),
+    #[error("invalid metadata key for the token header: {0:?}")]
+    InvalidHeaderName(String),
     #[error("parse metadata token: {0}")]
     ParseMetadataToken(String),
     #[error("poison error: {0}")]
     PoisonError(String),
-    #[error("execute failed")]
-    ExecuteFailed,
+    #[error("execute failed{}", describe_last_status(last_status))]
+    ExecuteFailed {
+        last_status: Option<Box<tonic::Status>>,
+    },
+    #[error("watch id {0} is already in use")]
+    DuplicateWatchId(i64),
+    #[error("leader member not found: {0}")]
+    LeaderNotFound(u64),
+    #[error("operation timed out")]
+    Timeout,
+    #[error("endpoint discovery failed: {0}")]
+    Discovery(String),
+    #[error("snapshot is corrupt: {0}")]
+    SnapshotCorrupt(String),
+    #[error("request of {size} bytes exceeds the limit of {limit} bytes")]
+    RequestTooLarge { size: usize, limit: usize },
+    #[error("revision {requested} is a future revision, the current revision is {current}")]
+    FutureRevision { requested: i64, current: i64 },
+    #[error("TLS is required to send credentials, but endpoint {0} is plaintext")]
+    TlsRequired(String),
+    #[error("response is missing its header")]
+    MissingHeader,
+    #[error("response is missing its {0}")]
+    MissingField(&'static str),
+    #[error("value of key {0:?} is not an integer")]
+    InvalidCounter(String),
+    #[error("incrementing the value of key {0:?} overflows")]
+    CounterOverflow(String),
+    #[error("gave up after {0} conflicting attempts")]
+    TooManyConflicts(u32),
+    #[error("failed to grant lease: {0}")]
+    LeaseGrantFailed(String),
+    #[error("circuit breaker is open")]
+    CircuitOpen,
+    #[error("{feature} is not supported by etcd {version}")]
+    Unsupported { feature: Feature, version: String },
+    #[error("session lease {0} has expired")]
+    SessionExpired(i64),
+    #[error("refusing to delete {count} keys, more than the limit of {limit}")]
+    DeleteTooLarge { count: u64, limit: u64 },
+    #[error("watch canceled by the server: {0:?}")]
+    WatchCanceled(CancelReason),
+}
+
+impl Error {
+    /// Returns the gRPC status the error was created from, if any.
+    pub fn status(&self) -> Option<&tonic::Status> {
+        match self {
+            Error::Response(status)
+            | Error::NotFound(status)
+            | Error::PermissionDenied(status)
+            | Error::Unauthenticated(status)
+            | Error::DeadlineExceeded(status)
+            | Error::ResourceExhausted(status)
+            | Error::AuthNotEnabled(status) => Some(status),
+            Error::ExecuteFailed {
+                last_status: Some(status),
+            } => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Whether the request failed because the leader was lost or changed while it was in flight.
+    ///
+    /// Reads are always retried by the client on such failures, and writes only when they were
+    /// definitely not applied. When the leader changed after a write was proposed, it may or may
+    /// not have been applied, so the error is returned and whether to retry is up to the caller.
+    pub fn is_leader_change(&self) -> bool {
+        self.status().is_some_and(is_leader_change)
+    }
+
+    /// Whether the error comes from the cluster being unreachable or without a leader for now, so
+    /// that trying again later may succeed, unlike with invalid configs or credentials.
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            Error::ExecuteFailed { .. } | Error::DeadlineExceeded(_) | Error::LeaderNotFound(_) => {
+                true
+            }
+            Error::AuthFailed { source, .. } => source.is_transient(),
+            e => e
+                .status()
+                .is_some_and(|status| status.code() == tonic::Code::Unavailable),
+        }
+    }
+}
+
+/// The messages etcd fails requests with when there is no leader to propose them to, in which
+/// case they were not applied.
+static NOT_APPLIED_LEADER_MESSAGES: [&str; 2] = ["etcdserver: not leader", "etcdserver: no leader"];
+
+/// The messages etcd fails requests with when the leader changed after they were proposed, in
+/// which case they may or may not have been applied.
+static AMBIGUOUS_LEADER_MESSAGES: [&str; 2] = [
+    "etcdserver: leader changed",
+    "etcdserver: request timed out, possibly due to previous leader failure",
+];
+
+pub(crate) fn is_leader_change(status: &tonic::Status) -> bool {
+    NOT_APPLIED_LEADER_MESSAGES
+        .iter()
+        .chain(&AMBIGUOUS_LEADER_MESSAGES)
+        .any(|msg| status.message().contains(msg))
+}
+
+/// Whether the request failed because the leader changed while it was in flight, without telling
+/// whether it was applied.
+pub(crate) fn is_ambiguous_leader_change(status: &tonic::Status) -> bool {
+    AMBIGUOUS_LEADER_MESSAGES
+        .iter()
+        .any(|msg| status.message().contains(msg))
+}
+
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        use tonic::Code;
+
+        // The status is boxed to keep results with an `Error` small.
+        let code = status.code();
+        let status = Box::new(status);
+        match code {
+            Code::FailedPrecondition
+                if status.message().contains("authentication is not enabled") =>
+            {
+                Error::AuthNotEnabled(status)
+            }
+            Code::NotFound => Error::NotFound(status),
+            Code::PermissionDenied => Error::PermissionDenied(status),
+            Code::Unauthenticated => Error::Unauthenticated(status),
+            Code::DeadlineExceeded => Error::DeadlineExceeded(status),
+            Code::ResourceExhausted => Error::ResourceExhausted(status),
+            _ => Error::Response(status),
+        }
+    }
+}
+
+fn describe_last_status(last_status: &Option<Box<tonic::Status>>) -> String {
+    match last_status {
+        Some(status) => format!(", last status: {status}"),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leader_change_classification() {
+        let not_leader = tonic::Status::failed_precondition("etcdserver: not leader");
+        let no_leader = tonic::Status::unavailable("etcdserver: no leader");
+        let changed = tonic::Status::unavailable("etcdserver: leader changed");
+        let timed_out = tonic::Status::unavailable(
+            "etcdserver: request timed out, possibly due to previous leader failure",
+        );
+        let other = tonic::Status::unavailable("transport error");
+
+        for status in [&not_leader, &no_leader, &changed, &timed_out] {
+            assert!(is_leader_change(status), "{status}");
+        }
+        assert!(!is_leader_change(&other));
+
+        assert!(!is_ambiguous_leader_change(&not_leader));
+        assert!(!is_ambiguous_leader_change(&no_leader));
+        assert!(is_ambiguous_leader_change(&changed));
+        assert!(is_ambiguous_leader_change(&timed_out));
+        assert!(Error::from(changed).is_leader_change());
+    }
+
+    #[test]
+    fn startup_errors_classification() {
+        let unreachable = Error::from(tonic::Status::unavailable("transport error"));
+        assert!(unreachable.is_transient());
+        assert!(Error::ExecuteFailed { last_status: None }.is_transient());
+        assert!(Error::AuthFailed {
+            username: "root".to_owned(),
+            source: Box::new(unreachable),
+        }
+        .is_transient());
+
+        let bad_password = Error::from(tonic::Status::invalid_argument(
+            "etcdserver: authentication failed, invalid user ID or password",
+        ));
+        assert!(!Error::AuthFailed {
+            username: "root".to_owned(),
+            source: Box::new(bad_password),
+        }
+        .is_transient());
+        assert!(!Error::TlsRequired("http://127.0.0.1:2379".to_owned()).is_transient());
+        assert!(!Error::InvalidURI("not a uri".parse::<http::Uri>().unwrap_err()).is_transient());
+    }
 }
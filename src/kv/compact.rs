@@ -1,5 +1,9 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug, Clone)]
 pub struct CompactRequest {
@@ -39,10 +43,47 @@ pub struct CompactResponse {
     pub header: ResponseHeader,
 }
 
-impl From<etcdserverpb::CompactionResponse> for CompactResponse {
-    fn from(proto: etcdserverpb::CompactionResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
-        }
+impl TryFrom<etcdserverpb::CompactionResponse> for CompactResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::CompactionResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
+    }
+}
+
+/// How much history an automatic compaction keeps.
+#[derive(Debug, Clone, Copy)]
+pub enum CompactRetention {
+    /// Keeps the revisions created within the given period.
+    Period(Duration),
+    /// Keeps the given number of the most recent revisions.
+    Revisions(i64),
+}
+
+/// Thresholds of the compaction triggered by deletes. See
+/// [`crate::ClientConfig::compact_after_deletes`].
+#[derive(Debug, Clone, Copy)]
+pub struct DeleteCompaction {
+    /// How many keys deleted through the client trigger a compaction.
+    pub deleted_keys: u64,
+    /// How many of the most recent revisions the compaction keeps.
+    pub keep_revisions: i64,
+}
+
+/// AutoCompactor is a handle of the background task started by `Client::auto_compact`.
+pub struct AutoCompactor {
+    task: JoinHandle<()>,
+}
+
+impl AutoCompactor {
+    pub(crate) fn new(task: JoinHandle<()>) -> Self {
+        Self { task }
+    }
+
+    /// Stops the automatic compaction.
+    pub fn stop(self) {
+        self.task.abort();
     }
 }
@@ -1,6 +1,6 @@
 use super::{KeyRange, KeyValue};
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug)]
 pub struct DeleteRequest {
@@ -28,6 +28,11 @@ impl DeleteRequest {
         self.proto.prev_kv = prev_kv;
         self
     }
+
+    /// Responds with the deleted key-value pairs, e.g. `DeleteRequest::new(KeyRange::prefix(p)).with_prev_kv()`.
+    pub fn with_prev_kv(self) -> Self {
+        self.prev_kv(true)
+    }
 }
 
 impl<T> From<T> for DeleteRequest
@@ -48,16 +53,20 @@ impl From<DeleteRequest> for etcdserverpb::DeleteRangeRequest {
 #[derive(Debug, Clone)]
 pub struct DeleteResponse {
     pub header: ResponseHeader,
+    /// The number of keys deleted by the request.
     pub deleted: u64,
+    /// The deleted key-value pairs, only filled when the request was made with `prev_kv`.
     pub prev_kvs: Vec<KeyValue>,
 }
 
-impl From<etcdserverpb::DeleteRangeResponse> for DeleteResponse {
-    fn from(proto: etcdserverpb::DeleteRangeResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::DeleteRangeResponse> for DeleteResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::DeleteRangeResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             deleted: proto.deleted as u64,
             prev_kvs: proto.prev_kvs.into_iter().map(From::from).collect(),
-        }
+        })
     }
 }
@@ -4,14 +4,18 @@ mod put;
 mod range;
 mod txn;
 
-pub use compact::{CompactRequest, CompactResponse};
+pub use compact::{
+    AutoCompactor, CompactRequest, CompactResponse, CompactRetention, DeleteCompaction,
+};
 pub use delete::{DeleteRequest, DeleteResponse};
 pub use put::{PutRequest, PutResponse};
-pub use range::{RangeRequest, RangeResponse};
-pub use txn::{TxnCmp, TxnOp, TxnOpResponse, TxnRequest, TxnResponse};
+pub use range::{RangeRequest, RangeResponse, SortOrder};
+pub use txn::{TxnCmp, TxnCompare, TxnOp, TxnOpResponse, TxnRequest, TxnResponse};
 
 use std::{future::Future, ops::Range};
 
+use bytes::Bytes;
+
 use crate::lease::LeaseId;
 use crate::proto::mvccpb;
 use crate::Result;
@@ -37,9 +41,15 @@ pub trait KeyValueOp {
     where
         R: Into<DeleteRequest>;
     fn delete_all(&self) -> impl Future<Output = Result<DeleteResponse>>;
+    /// Deletes the keys with the given prefix. To get the deleted key-value pairs back, use
+    /// `delete(DeleteRequest::new(KeyRange::prefix(p)).with_prev_kv())` instead. To guard against
+    /// deleting more than expected, see `Client::count_matching` and
+    /// `Client::delete_range_limited`.
     fn delete_by_prefix<K>(&self, p: K) -> impl Future<Output = Result<DeleteResponse>>
     where
         K: Into<Vec<u8>>;
+    /// Deletes the keys in `[from, end)`. To get the deleted key-value pairs back, use
+    /// `delete(DeleteRequest::new(KeyRange::range(from, end)).with_prev_kv())` instead.
     fn delete_range<F, E>(&self, from: F, end: E) -> impl Future<Output = Result<DeleteResponse>>
     where
         F: Into<Vec<u8>>,
@@ -54,11 +64,41 @@ pub trait KeyValueOp {
         R: Into<CompactRequest>;
 }
 
+/// Key is implemented by types which encode themselves as etcd keys, so that the key format of a
+/// domain type is defined once, e.g. `ServiceKey(id)` encoding to `/services/<id>`.
+pub trait Key {
+    fn to_key(&self) -> Vec<u8>;
+}
+
+impl Key for str {
+    fn to_key(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl Key for String {
+    fn to_key(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl Key for [u8] {
+    fn to_key(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl Key for Vec<u8> {
+    fn to_key(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
 /// Key-Value pair.
 #[derive(Clone, PartialEq, Default, Debug)]
 pub struct KeyValue {
     pub key: Vec<u8>,
-    pub value: Vec<u8>,
+    pub value: Bytes,
     pub create_revision: i64,
     pub mod_revision: i64,
     pub version: i64,
@@ -68,15 +108,32 @@ pub struct KeyValue {
 impl KeyValue {
     /// Converts the key from bytes `&[u8]` to `&str`.
     /// Leaves the original `&[u8]` in place, and creates a new string slice containing the entire content.
+    ///
+    /// # Panics
+    /// Panics if the key is not valid UTF-8. See `key_str_checked`.
     pub fn key_str(&self) -> &str {
         std::str::from_utf8(&self.key).expect("convert bytes to string")
     }
 
     /// Converts the value from bytes `&[u8]` to `&str`.
     /// Leaves the original `&[u8]` in place, and creates a new string slice containing the entire content.
+    ///
+    /// # Panics
+    /// Panics if the value is not valid UTF-8, e.g. binary data written by another client. See
+    /// `value_str_checked`.
     pub fn value_str(&self) -> &str {
         std::str::from_utf8(&self.value).expect("convert bytes to string")
     }
+
+    /// Converts the key from bytes `&[u8]` to `&str`, failing if it is not valid UTF-8.
+    pub fn key_str_checked(&self) -> Result<&str> {
+        Ok(std::str::from_utf8(&self.key)?)
+    }
+
+    /// Converts the value from bytes `&[u8]` to `&str`, failing if it is not valid UTF-8.
+    pub fn value_str_checked(&self) -> Result<&str> {
+        Ok(std::str::from_utf8(&self.value)?)
+    }
 }
 
 impl From<mvccpb::KeyValue> for KeyValue {
@@ -143,17 +200,14 @@ impl KeyRange {
             return KeyRange::all();
         }
 
-        let range_end = {
-            let mut end = key.clone();
-
-            for i in (0..end.len()).rev() {
-                if end[i] < 0xff {
-                    end[i] += 1;
-                    end.truncate(i + 1);
-                    break;
-                }
+        // Like etcd, a prefix made only of 0xff bytes ranges up to the end of the keyspace.
+        let range_end = match key.iter().rposition(|b| *b < 0xff) {
+            Some(i) => {
+                let mut end = key[..=i].to_vec();
+                end[i] += 1;
+                end
             }
-            end
+            None => vec![0],
         };
         Self { key, range_end }
     }
@@ -179,3 +233,35 @@ impl From<String> for KeyRange {
         Self::key(k)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_range_end_matches_etcdctl() {
+        // The range ends `etcdctl get --prefix` sends for each prefix.
+        let cases: [(&[u8], &[u8]); 7] = [
+            (b"foo", b"fop"),
+            (b"foo/", b"foo0"),
+            (b"a\xff", b"b"),
+            (b"a\xff\xff", b"b"),
+            (b"\x01\xfe", b"\x01\xff"),
+            (b"\xff", b"\x00"),
+            (b"\xff\xff", b"\x00"),
+        ];
+
+        for (prefix, range_end) in cases {
+            let range = KeyRange::prefix(prefix);
+            assert_eq!(range.key, prefix, "key of prefix {prefix:?}");
+            assert_eq!(range.range_end, range_end, "range end of prefix {prefix:?}");
+        }
+    }
+
+    #[test]
+    fn empty_prefix_is_all_keys() {
+        let range = KeyRange::prefix("");
+        assert_eq!(range.key, KeyRange::all().key);
+        assert_eq!(range.range_end, KeyRange::all().range_end);
+    }
+}
@@ -1,29 +1,44 @@
+use bytes::Bytes;
+
 use super::KeyValue;
 use crate::lease::LeaseId;
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Clone, Debug)]
 pub struct PutRequest {
     proto: etcdserverpb::PutRequest,
+    with_current: bool,
 }
 
 impl PutRequest {
     /// Creates a new PutRequest for saving the specified key-value.
+    ///
+    /// The value may be empty, in which case the key exists with an empty value. Unlike a delete,
+    /// this keeps the key visible to reads and sends a put event to watchers.
     pub fn new<K, V>(key: K, value: V) -> Self
     where
         K: Into<Vec<u8>>,
         V: Into<Vec<u8>>,
+    {
+        Self::from_bytes(key, value.into().into())
+    }
+
+    /// Creates a new PutRequest for saving the specified key-value, without copying the value.
+    pub fn from_bytes<K>(key: K, value: Bytes) -> Self
+    where
+        K: Into<Vec<u8>>,
     {
         Self {
             proto: etcdserverpb::PutRequest {
                 key: key.into(),
-                value: value.into(),
+                value,
                 lease: 0,
                 prev_kv: false,
                 ignore_value: false,
                 ignore_lease: false,
             },
+            with_current: false,
         }
     }
 
@@ -40,6 +55,23 @@ impl PutRequest {
         self
     }
 
+    /// When set, responds with the key-value as written, including its create revision and version.
+    ///
+    /// The put is then wrapped in a transaction which reads the key back, which is slightly more
+    /// expensive than a plain put but saves a separate get.
+    pub fn with_current(mut self) -> Self {
+        self.with_current = true;
+        self
+    }
+
+    pub(crate) fn is_with_current(&self) -> bool {
+        self.with_current
+    }
+
+    pub(crate) fn key(&self) -> &[u8] {
+        &self.proto.key
+    }
+
     /// When set, update the key without changing its current value. Returns an error if the key does not exist.
     pub fn ignore_value(mut self) -> Self {
         self.proto.ignore_value = true;
@@ -73,13 +105,31 @@ where
 pub struct PutResponse {
     pub header: ResponseHeader,
     pub prev_kv: KeyValue,
+    /// The key-value as written, only filled when the request was made with `with_current`.
+    pub current: Option<KeyValue>,
 }
 
-impl From<etcdserverpb::PutResponse> for PutResponse {
-    fn from(proto: etcdserverpb::PutResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::PutResponse> for PutResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::PutResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             prev_kv: From::from(proto.prev_kv.unwrap_or_default()),
-        }
+            current: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_value_is_sent_as_empty() {
+        let proto = etcdserverpb::PutRequest::from(PutRequest::new("k", ""));
+        assert_eq!(proto.key, b"k");
+        assert!(proto.value.is_empty());
+        assert!(!proto.ignore_value);
     }
 }
@@ -1,8 +1,10 @@
+use bytes::Bytes;
+
 use super::{KeyRange, KeyValue};
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RangeRequest {
     proto: etcdserverpb::RangeRequest,
 }
@@ -12,7 +14,7 @@ impl RangeRequest {
     pub fn new(key_range: KeyRange) -> Self {
         Self {
             proto: etcdserverpb::RangeRequest {
-                key: key_range.key,
+                key: key_range.key.into(),
                 range_end: key_range.range_end,
                 limit: 0,
                 revision: 0,
@@ -29,6 +31,13 @@ impl RangeRequest {
         }
     }
 
+    /// Creates a new RangeRequest for the specified key, without copying it.
+    pub fn from_bytes(key: Bytes) -> Self {
+        let mut req = Self::new(KeyRange::key(vec![]));
+        req.proto.key = key;
+        req
+    }
+
     /// Sets the maximum number of keys returned for the request.
     /// When limit is set to 0, it is treated as no limit.
     pub fn limit(mut self, limit: u64) -> Self {
@@ -36,6 +45,37 @@ impl RangeRequest {
         self
     }
 
+    /// When set, returns only the count of the keys in the range.
+    pub fn count_only(mut self) -> Self {
+        self.proto.count_only = true;
+        self
+    }
+
+    /// When set, returns only the keys and not their values.
+    pub fn keys_only(mut self) -> Self {
+        self.proto.keys_only = true;
+        self
+    }
+
+    /// When set, the request is served locally by the member it is sent to, without going through
+    /// consensus. Such reads are faster but may return stale data.
+    pub fn serializable(mut self) -> Self {
+        self.proto.serializable = true;
+        self
+    }
+
+    /// Filters out the keys last modified before the given revision.
+    pub fn min_mod_revision(mut self, revision: i64) -> Self {
+        self.proto.min_mod_revision = revision;
+        self
+    }
+
+    /// Filters out the keys last modified after the given revision.
+    pub fn max_mod_revision(mut self, revision: i64) -> Self {
+        self.proto.max_mod_revision = revision;
+        self
+    }
+
     pub fn revision(mut self, revision: i64) -> Self {
         self.proto.revision = revision;
         self
@@ -94,18 +134,47 @@ impl From<SortOrder> for i32 {
 #[derive(Debug, Clone)]
 pub struct RangeResponse {
     pub header: ResponseHeader,
+    /// The key-values of the range. A key holding an empty value is returned with an empty
+    /// `value`, while a missing key is not returned at all.
     pub kvs: Vec<KeyValue>,
     pub has_more: bool,
     pub count: u64,
 }
 
-impl From<etcdserverpb::RangeResponse> for RangeResponse {
-    fn from(proto: etcdserverpb::RangeResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::RangeResponse> for RangeResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::RangeResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             kvs: proto.kvs.into_iter().map(From::from).collect(),
             has_more: proto.more,
             count: proto.count as u64,
-        }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::mvccpb;
+
+    #[test]
+    fn key_with_empty_value_is_returned() {
+        let resp = RangeResponse::try_from(etcdserverpb::RangeResponse {
+            header: Some(Default::default()),
+            kvs: vec![mvccpb::KeyValue {
+                key: b"k".to_vec(),
+                version: 1,
+                ..Default::default()
+            }],
+            more: false,
+            count: 1,
+        })
+        .unwrap();
+
+        assert_eq!(resp.kvs.len(), 1);
+        assert_eq!(resp.kvs[0].key_str(), "k");
+        assert!(resp.kvs[0].value.is_empty());
     }
 }
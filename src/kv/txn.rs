@@ -1,8 +1,10 @@
 use super::{
-    DeleteRequest, DeleteResponse, KeyRange, PutRequest, PutResponse, RangeRequest, RangeResponse,
+    DeleteRequest, DeleteResponse, KeyRange, KeyValue, PutRequest, PutResponse, RangeRequest,
+    RangeResponse,
 };
+use crate::lease::LeaseId;
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 use etcdserverpb::compare::{CompareResult, CompareTarget, TargetUnion};
 use etcdserverpb::Compare;
 
@@ -23,64 +25,45 @@ impl TxnRequest {
         }
     }
 
-    /// Adds a version compare.
-    pub fn when_version(mut self, key_range: KeyRange, cmp: TxnCmp, version: usize) -> Self {
-        let result: CompareResult = cmp.into();
-        self.proto.compare.push(Compare {
-            result: result as i32,
-            target: CompareTarget::Version as i32,
-            key: key_range.key,
-            range_end: key_range.range_end,
-            target_union: Some(TargetUnion::Version(version as i64)),
-        });
+    /// Adds a compare. All compares must hold for the success operations to be executed.
+    pub fn when(mut self, compare: TxnCompare) -> Self {
+        self.proto.compare.push(compare.proto);
         self
     }
 
-    /// Adds a create revision compare.
-    pub fn when_create_revision(
-        mut self,
-        key_range: KeyRange,
-        cmp: TxnCmp,
-        revision: usize,
-    ) -> Self {
-        let result: CompareResult = cmp.into();
-        self.proto.compare.push(Compare {
-            result: result as i32,
-            target: CompareTarget::Create as i32,
-            key: key_range.key,
-            range_end: key_range.range_end,
-            target_union: Some(TargetUnion::CreateRevision(revision as i64)),
-        });
+    /// Adds several compares at once. Compares may target the same key, e.g. both its value and
+    /// its lease; each one is sent separately and all of them must hold.
+    pub fn when_all<I>(mut self, compares: I) -> Self
+    where
+        I: IntoIterator<Item = TxnCompare>,
+    {
+        self.proto
+            .compare
+            .extend(compares.into_iter().map(|compare| compare.proto));
         self
     }
 
+    /// Adds a version compare.
+    pub fn when_version(self, key_range: KeyRange, cmp: TxnCmp, version: usize) -> Self {
+        self.when(TxnCompare::version(key_range, cmp, version as i64))
+    }
+
+    /// Adds a create revision compare.
+    pub fn when_create_revision(self, key_range: KeyRange, cmp: TxnCmp, revision: usize) -> Self {
+        self.when(TxnCompare::create_revision(key_range, cmp, revision as i64))
+    }
+
     /// Adds a mod revision compare.
-    pub fn when_mod_revision(mut self, key_range: KeyRange, cmp: TxnCmp, revision: usize) -> Self {
-        let result: CompareResult = cmp.into();
-        self.proto.compare.push(Compare {
-            result: result as i32,
-            target: CompareTarget::Mod as i32,
-            key: key_range.key,
-            range_end: key_range.range_end,
-            target_union: Some(TargetUnion::ModRevision(revision as i64)),
-        });
-        self
+    pub fn when_mod_revision(self, key_range: KeyRange, cmp: TxnCmp, revision: usize) -> Self {
+        self.when(TxnCompare::mod_revision(key_range, cmp, revision as i64))
     }
 
     /// Adds a value compare.
-    pub fn when_value<V>(mut self, key_range: KeyRange, cmp: TxnCmp, value: V) -> Self
+    pub fn when_value<V>(self, key_range: KeyRange, cmp: TxnCmp, value: V) -> Self
     where
         V: Into<Vec<u8>>,
     {
-        let result: CompareResult = cmp.into();
-        self.proto.compare.push(Compare {
-            result: result as i32,
-            target: CompareTarget::Value as i32,
-            key: key_range.key,
-            range_end: key_range.range_end,
-            target_union: Some(TargetUnion::Value(value.into())),
-        });
-        self
+        self.when(TxnCompare::value(key_range, cmp, value))
     }
 
     /// If compare success, then execute the specified operations.
@@ -100,6 +83,60 @@ impl TxnRequest {
         self.proto.failure.push(op.into().into());
         self
     }
+
+    /// Makes the range operations serializable. A read-only transaction with only serializable
+    /// ranges is served by the member it is sent to without going through consensus, which
+    /// scales reads at the cost of possibly stale results, and is routed according to the
+    /// `ReadPreference` of the client. Transactions with writes always go through consensus.
+    pub fn serializable(mut self) -> Self {
+        fn mark(ops: &mut [etcdserverpb::RequestOp]) {
+            use etcdserverpb::request_op::Request;
+
+            for op in ops {
+                match &mut op.request {
+                    Some(Request::RequestRange(range)) => range.serializable = true,
+                    Some(Request::RequestTxn(txn)) => {
+                        mark(&mut txn.success);
+                        mark(&mut txn.failure);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        mark(&mut self.proto.success);
+        mark(&mut self.proto.failure);
+        self
+    }
+
+    /// Whether the transaction only reads, in both branches and nested transactions.
+    pub fn is_read_only(&self) -> bool {
+        self.all_ranges(|_| true)
+    }
+
+    /// Whether the transaction only reads, and all its reads are serializable.
+    pub(crate) fn is_serializable_read(&self) -> bool {
+        self.all_ranges(|range| range.serializable)
+    }
+
+    fn all_ranges(&self, pred: impl Fn(&etcdserverpb::RangeRequest) -> bool + Copy) -> bool {
+        fn check(
+            ops: &[etcdserverpb::RequestOp],
+            pred: impl Fn(&etcdserverpb::RangeRequest) -> bool + Copy,
+        ) -> bool {
+            use etcdserverpb::request_op::Request;
+
+            ops.iter().all(|op| match &op.request {
+                Some(Request::RequestRange(range)) => pred(range),
+                Some(Request::RequestTxn(txn)) => {
+                    check(&txn.success, pred) && check(&txn.failure, pred)
+                }
+                _ => false,
+            })
+        }
+
+        check(&self.proto.success, pred) && check(&self.proto.failure, pred)
+    }
 }
 
 impl Default for TxnRequest {
@@ -114,6 +151,80 @@ impl From<TxnRequest> for crate::proto::etcdserverpb::TxnRequest {
     }
 }
 
+/// A condition on the keys of a range, evaluated by a transaction.
+#[derive(Debug, Clone)]
+pub struct TxnCompare {
+    proto: Compare,
+}
+
+impl TxnCompare {
+    fn new(key_range: KeyRange, cmp: TxnCmp, target: CompareTarget, union: TargetUnion) -> Self {
+        let result: CompareResult = cmp.into();
+        Self {
+            proto: Compare {
+                result: result as i32,
+                target: target as i32,
+                key: key_range.key,
+                range_end: key_range.range_end,
+                target_union: Some(union),
+            },
+        }
+    }
+
+    /// Compares the version of the keys.
+    pub fn version(key_range: KeyRange, cmp: TxnCmp, version: i64) -> Self {
+        Self::new(
+            key_range,
+            cmp,
+            CompareTarget::Version,
+            TargetUnion::Version(version),
+        )
+    }
+
+    /// Compares the create revision of the keys.
+    pub fn create_revision(key_range: KeyRange, cmp: TxnCmp, revision: i64) -> Self {
+        Self::new(
+            key_range,
+            cmp,
+            CompareTarget::Create,
+            TargetUnion::CreateRevision(revision),
+        )
+    }
+
+    /// Compares the mod revision of the keys.
+    pub fn mod_revision(key_range: KeyRange, cmp: TxnCmp, revision: i64) -> Self {
+        Self::new(
+            key_range,
+            cmp,
+            CompareTarget::Mod,
+            TargetUnion::ModRevision(revision),
+        )
+    }
+
+    /// Compares the value of the keys.
+    pub fn value<V>(key_range: KeyRange, cmp: TxnCmp, value: V) -> Self
+    where
+        V: Into<Vec<u8>>,
+    {
+        Self::new(
+            key_range,
+            cmp,
+            CompareTarget::Value,
+            TargetUnion::Value(value.into()),
+        )
+    }
+
+    /// Compares the lease of the keys.
+    pub fn lease(key_range: KeyRange, cmp: TxnCmp, lease: LeaseId) -> Self {
+        Self::new(
+            key_range,
+            cmp,
+            CompareTarget::Lease,
+            TargetUnion::Lease(lease),
+        )
+    }
+}
+
 /// Transaction Operation.
 pub enum TxnOp {
     Range(RangeRequest),
@@ -189,15 +300,23 @@ pub enum TxnOpResponse {
     Txn(TxnResponse),
 }
 
-impl From<etcdserverpb::ResponseOp> for TxnOpResponse {
-    fn from(mut resp: etcdserverpb::ResponseOp) -> Self {
+impl TryFrom<etcdserverpb::ResponseOp> for TxnOpResponse {
+    type Error = Error;
+
+    fn try_from(mut resp: etcdserverpb::ResponseOp) -> Result<Self> {
         use etcdserverpb::response_op::Response;
-        match resp.response.take().unwrap() {
-            Response::ResponseRange(r) => Self::Range(From::from(r)),
-            Response::ResponsePut(r) => Self::Put(From::from(r)),
-            Response::ResponseTxn(r) => Self::Txn(From::from(r)),
-            Response::ResponseDeleteRange(r) => Self::Delete(From::from(r)),
-        }
+        Ok(
+            match resp
+                .response
+                .take()
+                .ok_or(Error::MissingField("response"))?
+            {
+                Response::ResponseRange(r) => Self::Range(r.try_into()?),
+                Response::ResponsePut(r) => Self::Put(r.try_into()?),
+                Response::ResponseTxn(r) => Self::Txn(r.try_into()?),
+                Response::ResponseDeleteRange(r) => Self::Delete(r.try_into()?),
+            },
+        )
     }
 }
 
@@ -208,12 +327,282 @@ pub struct TxnResponse {
     pub responses: Vec<TxnOpResponse>,
 }
 
-impl From<etcdserverpb::TxnResponse> for TxnResponse {
-    fn from(proto: etcdserverpb::TxnResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<etcdserverpb::TxnResponse> for TxnResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::TxnResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             succeeded: proto.succeeded,
-            responses: proto.responses.into_iter().map(From::from).collect(),
-        }
+            responses: proto
+                .responses
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl TxnResponse {
+    /// Returns the key-value pairs read by the get operations of the executed branch.
+    ///
+    /// The pairs are complete, including the value and `mod_revision`, so a compare-and-swap
+    /// which fell into an else-branch get can retry against the current value right away.
+    pub fn range_kvs(&self) -> impl Iterator<Item = &KeyValue> {
+        self.responses.iter().flat_map(|resp| match resp {
+            TxnOpResponse::Range(range) => range.kvs.as_slice(),
+            _ => &[],
+        })
+    }
+}
+
+/// Builds a `TxnRequest` from a declarative description of its compares and operations.
+///
+/// Compares are written `target(key) op operand`, with the targets `value`, `version`,
+/// `create_revision`, `mod_revision` and `lease`, and the operators `==`, `!=`, `<` and `>`.
+/// Several compares are joined with `&&`, and operands which are not a single literal or
+/// variable must be parenthesized. Operations are `get(key)`, `put(key, value)` and
+/// `delete(key)`, each followed by a semicolon, and nested transactions `txn { ... }`. Without
+/// `if`, the operations run unconditionally. Either branch may be empty.
+///
+/// ```
+/// use ya_etcd_rs::{txn, TxnRequest};
+///
+/// let rev = 42;
+/// let req: TxnRequest = txn! {
+///     if value("k") == "a" && mod_revision("lock") < (rev + 1) {
+///         put("k", "b");
+///         delete("lock");
+///     } else {
+///         get("k");
+///         txn {
+///             if version("k") == 0 {
+///                 put("k", "a");
+///             }
+///         }
+///     }
+/// };
+/// ```
+#[macro_export]
+macro_rules! txn {
+    (@cmp $target:ident, $key:expr, ==, $rhs:expr) => {
+        $crate::TxnCompare::$target($crate::KeyRange::key($key), $crate::TxnCmp::Equal, $rhs)
+    };
+    (@cmp $target:ident, $key:expr, !=, $rhs:expr) => {
+        $crate::TxnCompare::$target($crate::KeyRange::key($key), $crate::TxnCmp::NotEqual, $rhs)
+    };
+    (@cmp $target:ident, $key:expr, <, $rhs:expr) => {
+        $crate::TxnCompare::$target($crate::KeyRange::key($key), $crate::TxnCmp::Less, $rhs)
+    };
+    (@cmp $target:ident, $key:expr, >, $rhs:expr) => {
+        $crate::TxnCompare::$target($crate::KeyRange::key($key), $crate::TxnCmp::Greater, $rhs)
+    };
+
+    (@ops $req:expr, $branch:ident;) => {
+        $req
+    };
+    (@ops $req:expr, $branch:ident; get($key:expr); $($rest:tt)*) => {
+        $crate::txn!(@ops
+            $req.$branch($crate::RangeRequest::new($crate::KeyRange::key($key))), $branch;
+            $($rest)*)
+    };
+    (@ops $req:expr, $branch:ident; put($key:expr, $value:expr); $($rest:tt)*) => {
+        $crate::txn!(@ops $req.$branch($crate::PutRequest::new($key, $value)), $branch; $($rest)*)
+    };
+    (@ops $req:expr, $branch:ident; txn { $($inner:tt)* } $($rest:tt)*) => {
+        $crate::txn!(@ops $req.$branch($crate::txn!($($inner)*)), $branch; $($rest)*)
+    };
+    (@ops $req:expr, $branch:ident; delete($key:expr); $($rest:tt)*) => {
+        $crate::txn!(@ops
+            $req.$branch($crate::DeleteRequest::new($crate::KeyRange::key($key))), $branch;
+            $($rest)*)
+    };
+
+    (@cond $req:expr; $target:ident($key:expr) $op:tt $rhs:tt && $($rest:tt)*) => {
+        $crate::txn!(@cond $req.when($crate::txn!(@cmp $target, $key, $op, $rhs)); $($rest)*)
+    };
+    (@cond $req:expr; $target:ident($key:expr) $op:tt $rhs:tt
+        { $($then:tt)* } else { $($otherwise:tt)* }) => {
+        $crate::txn!(@ops
+            $crate::txn!(@ops
+                $req.when($crate::txn!(@cmp $target, $key, $op, $rhs)), and_then; $($then)*),
+            or_else; $($otherwise)*)
+    };
+    (@cond $req:expr; $target:ident($key:expr) $op:tt $rhs:tt { $($then:tt)* }) => {
+        $crate::txn!(@ops
+            $req.when($crate::txn!(@cmp $target, $key, $op, $rhs)), and_then; $($then)*)
+    };
+
+    (if $($rest:tt)*) => {
+        $crate::txn!(@cond $crate::TxnRequest::new(); $($rest)*)
+    };
+    ($($ops:tt)*) => {
+        $crate::txn!(@ops $crate::TxnRequest::new(), and_then; $($ops)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_all_sends_each_compare_of_one_key() {
+        let req = TxnRequest::new().when_all([
+            TxnCompare::value(KeyRange::key("lock"), TxnCmp::Equal, "holder"),
+            TxnCompare::lease(KeyRange::key("lock"), TxnCmp::Equal, 7),
+        ]);
+        let proto = etcdserverpb::TxnRequest::from(req);
+
+        assert_eq!(proto.compare.len(), 2);
+        assert!(proto.compare.iter().all(|cmp| cmp.key == b"lock"));
+        assert_eq!(
+            proto.compare[0].target_union,
+            Some(TargetUnion::Value(b"holder".to_vec()))
+        );
+        assert_eq!(proto.compare[1].target_union, Some(TargetUnion::Lease(7)));
+        assert_eq!(proto.compare[1].target, CompareTarget::Lease as i32);
+    }
+
+    #[test]
+    fn range_kvs_of_failed_compare_are_complete() {
+        use crate::proto::mvccpb;
+        use etcdserverpb::response_op::Response;
+
+        let header = Some(etcdserverpb::ResponseHeader::default());
+        let resp = TxnResponse::try_from(etcdserverpb::TxnResponse {
+            header,
+            succeeded: false,
+            responses: vec![etcdserverpb::ResponseOp {
+                response: Some(Response::ResponseRange(etcdserverpb::RangeResponse {
+                    header,
+                    kvs: vec![mvccpb::KeyValue {
+                        key: b"k".to_vec(),
+                        value: bytes::Bytes::from_static(b"current"),
+                        mod_revision: 9,
+                        ..Default::default()
+                    }],
+                    count: 1,
+                    more: false,
+                })),
+            }],
+        })
+        .unwrap();
+
+        let kvs: Vec<_> = resp.range_kvs().collect();
+        assert_eq!(kvs.len(), 1);
+        assert_eq!(kvs[0].value_str(), "current");
+        assert_eq!(kvs[0].mod_revision, 9);
+    }
+
+    fn ops(ops: &[etcdserverpb::RequestOp]) -> Vec<&'static str> {
+        use etcdserverpb::request_op::Request;
+
+        ops.iter()
+            .map(|op| match op.request {
+                Some(Request::RequestRange(_)) => "get",
+                Some(Request::RequestPut(_)) => "put",
+                Some(Request::RequestDeleteRange(_)) => "delete",
+                Some(Request::RequestTxn(_)) => "txn",
+                None => "none",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn txn_macro_unconditional_ops() {
+        let proto = etcdserverpb::TxnRequest::from(crate::txn! {
+            get("a");
+            put("b", "1");
+            delete("c");
+        });
+
+        assert!(proto.compare.is_empty());
+        assert_eq!(ops(&proto.success), ["get", "put", "delete"]);
+        assert!(proto.failure.is_empty());
+    }
+
+    #[test]
+    fn txn_macro_compare_only() {
+        let proto = etcdserverpb::TxnRequest::from(crate::txn! {
+            if value("k") == "a" && version("k") > 1 {}
+        });
+
+        assert_eq!(proto.compare.len(), 2);
+        assert_eq!(proto.compare[0].target, CompareTarget::Value as i32);
+        assert_eq!(proto.compare[1].target, CompareTarget::Version as i32);
+        assert_eq!(proto.compare[1].result, CompareResult::Greater as i32);
+        assert!(proto.success.is_empty());
+        assert!(proto.failure.is_empty());
+    }
+
+    #[test]
+    fn txn_macro_then_only() {
+        let proto = etcdserverpb::TxnRequest::from(crate::txn! {
+            if lease("k") != 7 {
+                put("k", "v");
+            }
+        });
+
+        assert_eq!(proto.compare[0].result, CompareResult::NotEqual as i32);
+        assert_eq!(proto.compare[0].target_union, Some(TargetUnion::Lease(7)));
+        assert_eq!(ops(&proto.success), ["put"]);
+        assert!(proto.failure.is_empty());
+    }
+
+    #[test]
+    fn txn_macro_else_only() {
+        let proto = etcdserverpb::TxnRequest::from(crate::txn! {
+            if create_revision("k") == 0 {} else {
+                get("k");
+            }
+        });
+
+        assert_eq!(proto.compare[0].target, CompareTarget::Create as i32);
+        assert!(proto.success.is_empty());
+        assert_eq!(ops(&proto.failure), ["get"]);
+    }
+
+    #[test]
+    fn txn_macro_nested() {
+        let rev = 4;
+        let proto = etcdserverpb::TxnRequest::from(crate::txn! {
+            if mod_revision("lock") < (rev + 1) {
+                delete("lock");
+                txn {
+                    if value("k") == "a" {
+                        put("k", "b");
+                    } else {
+                        get("k");
+                    }
+                }
+            } else {
+                txn {
+                    put("k", "c");
+                }
+            }
+        });
+
+        assert_eq!(
+            proto.compare[0].target_union,
+            Some(TargetUnion::ModRevision(5))
+        );
+        assert_eq!(ops(&proto.success), ["delete", "txn"]);
+        assert_eq!(ops(&proto.failure), ["txn"]);
+
+        let Some(etcdserverpb::request_op::Request::RequestTxn(inner)) = &proto.success[1].request
+        else {
+            unreachable!();
+        };
+        assert_eq!(inner.compare.len(), 1);
+        assert_eq!(ops(&inner.success), ["put"]);
+        assert_eq!(ops(&inner.failure), ["get"]);
+    }
+
+    #[test]
+    fn response_op_without_response_is_an_error() {
+        assert!(matches!(
+            TxnOpResponse::try_from(etcdserverpb::ResponseOp::default()),
+            Err(Error::MissingField(_))
+        ));
     }
 }
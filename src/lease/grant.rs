@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use crate::lease::LeaseId;
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug)]
 pub struct LeaseGrantRequest {
@@ -42,16 +42,36 @@ impl From<Duration> for LeaseGrantRequest {
 #[derive(Debug, Clone)]
 pub struct LeaseGrantResponse {
     pub header: ResponseHeader,
+    /// The ID of the granted lease.
     pub id: LeaseId,
+    /// The TTL chosen by the server in seconds, which may differ from the requested one.
     pub ttl: i64,
 }
 
-impl From<crate::proto::etcdserverpb::LeaseGrantResponse> for LeaseGrantResponse {
-    fn from(proto: crate::proto::etcdserverpb::LeaseGrantResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl LeaseGrantResponse {
+    /// Get the ID of the granted lease.
+    pub fn id(&self) -> LeaseId {
+        self.id
+    }
+
+    /// Get the TTL chosen by the server in seconds.
+    pub fn ttl(&self) -> i64 {
+        self.ttl
+    }
+}
+
+impl TryFrom<crate::proto::etcdserverpb::LeaseGrantResponse> for LeaseGrantResponse {
+    type Error = Error;
+
+    fn try_from(proto: crate::proto::etcdserverpb::LeaseGrantResponse) -> Result<Self> {
+        if !proto.error.is_empty() {
+            return Err(Error::LeaseGrantFailed(proto.error));
+        }
+
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             id: proto.id,
             ttl: proto.ttl,
-        }
+        })
     }
 }
@@ -1,6 +1,6 @@
 use crate::lease::LeaseId;
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug)]
 pub struct LeaseKeepAliveRequest {
@@ -29,12 +29,14 @@ pub struct LeaseKeepAliveResponse {
     pub ttl: i64,
 }
 
-impl From<crate::proto::etcdserverpb::LeaseKeepAliveResponse> for LeaseKeepAliveResponse {
-    fn from(proto: crate::proto::etcdserverpb::LeaseKeepAliveResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<crate::proto::etcdserverpb::LeaseKeepAliveResponse> for LeaseKeepAliveResponse {
+    type Error = Error;
+
+    fn try_from(proto: crate::proto::etcdserverpb::LeaseKeepAliveResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             id: proto.id,
             ttl: proto.ttl,
-        }
+        })
     }
 }
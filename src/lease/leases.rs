@@ -0,0 +1,50 @@
+use crate::proto::etcdserverpb;
+use crate::{Error, LeaseId, ResponseHeader, Result};
+
+#[derive(Debug, Clone, Default)]
+pub struct LeaseLeasesRequest {
+    proto: etcdserverpb::LeaseLeasesRequest,
+}
+
+impl LeaseLeasesRequest {
+    /// Creates a new LeaseLeasesRequest which lists all existing leases.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl From<LeaseLeasesRequest> for etcdserverpb::LeaseLeasesRequest {
+    fn from(req: LeaseLeasesRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeaseLeasesResponse {
+    pub header: ResponseHeader,
+    pub leases: Vec<LeaseId>,
+}
+
+impl TryFrom<etcdserverpb::LeaseLeasesResponse> for LeaseLeasesResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::LeaseLeasesResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            leases: proto.leases.into_iter().map(|lease| lease.id).collect(),
+        })
+    }
+}
+
+/// The number of leases along with the size of the backend of the member serving the requests,
+/// to correlate lease growth with backend growth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeaseStats {
+    /// The number of existing leases.
+    pub leases: usize,
+    /// The size of the backend database, in bytes.
+    pub db_size: i64,
+    /// The size of the backend database in use, in bytes, which is smaller than `db_size` until
+    /// the freed space is reclaimed by a defragmentation.
+    pub db_size_in_use: i64,
+}
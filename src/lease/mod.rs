@@ -6,23 +6,37 @@
 
 mod grant;
 mod keep_alive;
+mod leases;
 mod revoke;
 mod time_to_live;
 
 pub use grant::{LeaseGrantRequest, LeaseGrantResponse};
 pub use keep_alive::{LeaseKeepAliveRequest, LeaseKeepAliveResponse};
+pub use leases::{LeaseLeasesRequest, LeaseLeasesResponse, LeaseStats};
 pub use revoke::{LeaseRevokeRequest, LeaseRevokeResponse};
 pub use time_to_live::{LeaseTimeToLiveRequest, LeaseTimeToLiveResponse};
 
-use std::future::Future;
+use std::{collections::HashMap, future::Future, time::Duration};
 
-use tokio::sync::mpsc::Sender;
+use tokio::{
+    sync::{
+        mpsc::{Sender, UnboundedReceiver},
+        watch,
+    },
+    task::JoinHandle,
+    time::Instant,
+};
 use tonic::Streaming;
 
-use crate::{Error, Result};
+use crate::{Client, Error, Result};
 
 pub type LeaseId = i64;
 
+/// The delays between the attempts to reopen a broken keep-alive stream, doubled on every failed
+/// attempt up to the maximum.
+static REOPEN_MIN_BACKOFF: Duration = Duration::from_millis(100);
+static REOPEN_MAX_BACKOFF: Duration = Duration::from_secs(1);
+
 pub trait LeaseOp {
     fn grant_lease<R>(&self, req: R) -> impl Future<Output = Result<LeaseGrantResponse>>
     where
@@ -34,13 +48,24 @@ pub trait LeaseOp {
 
     fn keep_alive_for(&self, lease_id: LeaseId) -> impl Future<Output = Result<LeaseKeepAlive>>;
 
+    /// Keeps many leases alive over a single keep-alive stream, refreshed once per call of
+    /// `MultiKeepAlive::keep_alive`, or in the background with `MultiKeepAlive::spawn`.
+    fn keep_alive_many(
+        &self,
+        lease_ids: Vec<LeaseId>,
+    ) -> impl Future<Output = Result<MultiKeepAlive>>;
+
     fn time_to_live<R>(&self, req: R) -> impl Future<Output = Result<LeaseTimeToLiveResponse>>
     where
         R: Into<LeaseTimeToLiveRequest> + Send;
+
+    /// Lists the IDs of all existing leases.
+    fn lease_leases(&self) -> impl Future<Output = Result<LeaseLeasesResponse>>;
 }
 
 pub struct LeaseKeepAlive {
     id: LeaseId,
+    client: Client,
     req_tx: Sender<crate::proto::etcdserverpb::LeaseKeepAliveRequest>,
     resp_rx: Streaming<crate::proto::etcdserverpb::LeaseKeepAliveResponse>,
 }
@@ -48,11 +73,13 @@ pub struct LeaseKeepAlive {
 impl LeaseKeepAlive {
     pub(crate) fn new(
         id: LeaseId,
+        client: Client,
         req_tx: Sender<crate::proto::etcdserverpb::LeaseKeepAliveRequest>,
         resp_rx: Streaming<crate::proto::etcdserverpb::LeaseKeepAliveResponse>,
     ) -> Self {
         Self {
             id,
+            client,
             req_tx,
             resp_rx,
         }
@@ -71,6 +98,356 @@ impl LeaseKeepAlive {
             .await
             .map_err(|_| Error::ChannelClosed)?;
 
-        Ok(self.resp_rx.message().await?.map(|resp| resp.into()))
+        self.resp_rx
+            .message()
+            .await?
+            .map(TryFrom::try_from)
+            .transpose()
+    }
+
+    /// Closes the keep-alive stream, then revokes the lease, which deletes the keys attached to
+    /// it.
+    pub async fn revoke(self) -> Result<()> {
+        let Self {
+            id,
+            client,
+            req_tx,
+            resp_rx,
+        } = self;
+        drop(req_tx);
+        drop(resp_rx);
+        client.revoke(LeaseRevokeRequest::new(id)).await?;
+
+        Ok(())
+    }
+
+    /// Keeps the lease alive from a background task until the returned handle is stopped.
+    ///
+    /// The lease is refreshed after a third of the TTL granted by the server in the previous
+    /// response, so a TTL shrunk by the server under load is followed.
+    ///
+    /// A broken keep-alive stream is reopened with backoff. Once the TTL runs out without a
+    /// refresh, the lease is reported expired, but the stream is still reopened, and the task only
+    /// ends when the server reports that the lease expired or was revoked.
+    pub fn spawn(self) -> AutoKeepAlive {
+        let id = self.id;
+        let client = self.client.clone();
+        let (state_tx, state_rx) = watch::channel((id, None));
+
+        let mut keep_alive = self;
+        let task = tokio::spawn(async move {
+            let mut expires_at = None;
+            let mut backoff = REOPEN_MIN_BACKOFF;
+            loop {
+                match keep_alive.keep_alive().await {
+                    Ok(Some(resp)) if resp.ttl > 0 => {
+                        let ttl = Duration::from_secs(resp.ttl as u64);
+                        expires_at = Some(Instant::now() + ttl);
+                        backoff = REOPEN_MIN_BACKOFF;
+                        state_tx.send_replace((id, Some(resp.ttl)));
+                        tokio::time::sleep(ttl / 3).await;
+                    }
+                    Ok(Some(_)) => {
+                        state_tx.send_replace((id, Some(0)));
+                        break;
+                    }
+                    Ok(None) | Err(_) => {
+                        if expires_at.is_some_and(|at| Instant::now() >= at) {
+                            state_tx.send_replace((id, Some(0)));
+                        }
+
+                        // A broken stream does not mean the lease is lost, so the lease is
+                        // reattached until the server reports whether it expired.
+                        match keep_alive.client.keep_alive_for(id).await {
+                            Ok(reopened) => keep_alive = reopened,
+                            Err(_) => {
+                                tokio::time::sleep(backoff).await;
+                                backoff = (backoff * 2).min(REOPEN_MAX_BACKOFF);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        AutoKeepAlive::new(client, state_rx, None, task)
+    }
+}
+
+/// LeaseRegranted reports that a lost lease was replaced by a new one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaseRegranted {
+    /// The lease which was lost.
+    pub previous: LeaseId,
+    /// The newly granted lease the keys are now attached to.
+    pub lease_id: LeaseId,
+}
+
+/// AutoKeepAlive is a handle of the background task started by `LeaseKeepAlive::spawn` or
+/// `Client::keep_alive_regranting`.
+pub struct AutoKeepAlive {
+    client: Client,
+    state_rx: watch::Receiver<(LeaseId, Option<i64>)>,
+    regranted_rx: Option<UnboundedReceiver<LeaseRegranted>>,
+    task: JoinHandle<()>,
+}
+
+impl AutoKeepAlive {
+    pub(crate) fn new(
+        client: Client,
+        state_rx: watch::Receiver<(LeaseId, Option<i64>)>,
+        regranted_rx: Option<UnboundedReceiver<LeaseRegranted>>,
+        task: JoinHandle<()>,
+    ) -> Self {
+        Self {
+            client,
+            state_rx,
+            regranted_rx,
+            task,
+        }
+    }
+
+    /// Returns the ID of the lease currently kept alive, which changes when the lease is regranted.
+    #[inline]
+    pub fn lease_id(&self) -> LeaseId {
+        self.state_rx.borrow().0
+    }
+
+    /// Returns the TTL granted by the server on the last refresh, or `None` before the first one.
+    /// A TTL of 0 means the lease has expired or could not be refreshed before its TTL ran out.
+    /// Unless the lease is regranted, the task ends once the server reports it expired.
+    pub fn ttl(&self) -> Option<i64> {
+        self.state_rx.borrow().1
+    }
+
+    /// Waits until the lease is reported expired, i.e. its TTL is 0, or the task has ended.
+    pub async fn expired(&self) {
+        let mut state_rx = self.state_rx.clone();
+        let _ = state_rx.wait_for(|(_, ttl)| *ttl == Some(0)).await;
+    }
+
+    /// Waits for the next time the lease is regranted.
+    /// Returns `None` if regranting is not enabled or the task has ended.
+    pub async fn regranted(&mut self) -> Option<LeaseRegranted> {
+        match self.regranted_rx.as_mut() {
+            Some(rx) => rx.recv().await,
+            None => None,
+        }
+    }
+
+    /// Stops refreshing the lease. The lease itself expires once its TTL elapses.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+
+    /// Stops refreshing the lease and revokes it, which deletes the keys attached to it. The
+    /// background task is waited for first, so that no refresh or regrant races the revocation.
+    pub async fn revoke(self) -> Result<()> {
+        self.task.abort();
+        let _ = self.task.await;
+
+        let lease_id = self.state_rx.borrow().0;
+        self.client
+            .revoke(LeaseRevokeRequest::new(lease_id))
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// MultiKeepAlive refreshes a set of leases over one shared keep-alive stream.
+pub struct MultiKeepAlive {
+    ids: Vec<LeaseId>,
+    client: Client,
+    req_tx: Sender<crate::proto::etcdserverpb::LeaseKeepAliveRequest>,
+    resp_rx: Streaming<crate::proto::etcdserverpb::LeaseKeepAliveResponse>,
+}
+
+impl MultiKeepAlive {
+    pub(crate) fn new(
+        ids: Vec<LeaseId>,
+        client: Client,
+        req_tx: Sender<crate::proto::etcdserverpb::LeaseKeepAliveRequest>,
+        resp_rx: Streaming<crate::proto::etcdserverpb::LeaseKeepAliveResponse>,
+    ) -> Self {
+        Self {
+            ids,
+            client,
+            req_tx,
+            resp_rx,
+        }
+    }
+
+    #[inline]
+    pub fn lease_ids(&self) -> &[LeaseId] {
+        &self.ids
+    }
+
+    /// Refreshes every lease once and returns one response per lease.
+    /// A lease whose response has a TTL of 0 or less has expired.
+    pub async fn keep_alive(&mut self) -> Result<Vec<LeaseKeepAliveResponse>> {
+        for id in self.ids.iter() {
+            self.req_tx
+                .send(LeaseKeepAliveRequest::new(*id).into())
+                .await
+                .map_err(|_| Error::ChannelClosed)?;
+        }
+
+        self.receive().await
+    }
+
+    /// Keeps the leases alive from a background task until the returned handle is stopped.
+    ///
+    /// Every round refreshes all the leases still alive, after a third of the smallest TTL
+    /// granted by the server in the previous round. A lease reported with a TTL of 0 or less has
+    /// expired and is no longer refreshed, and the task ends once all of them have. A broken
+    /// stream is reopened with backoff, as with `LeaseKeepAlive::spawn`.
+    pub fn spawn(self) -> MultiAutoKeepAlive {
+        let client = self.client.clone();
+        let (state_tx, state_rx) = watch::channel(
+            self.ids
+                .iter()
+                .map(|id| (*id, None))
+                .collect::<HashMap<_, _>>(),
+        );
+
+        let mut keep_alive = self;
+        let task = tokio::spawn(async move {
+            let mut expires_at = HashMap::new();
+            let mut backoff = REOPEN_MIN_BACKOFF;
+            while !keep_alive.ids.is_empty() {
+                match keep_alive.keep_alive().await {
+                    Ok(resps) => {
+                        let now = Instant::now();
+                        for resp in resps.iter().filter(|resp| resp.ttl > 0) {
+                            expires_at.insert(resp.id, now + Duration::from_secs(resp.ttl as u64));
+                        }
+                        keep_alive
+                            .ids
+                            .retain(|id| resps.iter().any(|resp| resp.id == *id && resp.ttl > 0));
+                        backoff = REOPEN_MIN_BACKOFF;
+                        state_tx.send_modify(|ttls| {
+                            for resp in resps.iter() {
+                                ttls.insert(resp.id, Some(resp.ttl.max(0)));
+                            }
+                        });
+
+                        let min_ttl = resps
+                            .iter()
+                            .map(|resp| resp.ttl)
+                            .filter(|ttl| *ttl > 0)
+                            .min();
+                        if let Some(ttl) = min_ttl {
+                            tokio::time::sleep(Duration::from_secs(ttl as u64) / 3).await;
+                        }
+                    }
+                    Err(_) => {
+                        let now = Instant::now();
+                        state_tx.send_modify(|ttls| {
+                            for (id, at) in expires_at.iter() {
+                                if now >= *at {
+                                    ttls.insert(*id, Some(0));
+                                }
+                            }
+                        });
+
+                        // A broken stream does not mean the leases are lost, so they are
+                        // reattached until the server reports whether they expired.
+                        let ids = keep_alive.ids.clone();
+                        match keep_alive.client.keep_alive_many(ids).await {
+                            Ok(reopened) => keep_alive = reopened,
+                            Err(_) => {
+                                tokio::time::sleep(backoff).await;
+                                backoff = (backoff * 2).min(REOPEN_MAX_BACKOFF);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        MultiAutoKeepAlive {
+            client,
+            state_rx,
+            task,
+        }
+    }
+
+    pub(crate) async fn receive(&mut self) -> Result<Vec<LeaseKeepAliveResponse>> {
+        let mut resps = Vec::with_capacity(self.ids.len());
+        while resps.len() < self.ids.len() {
+            match self.resp_rx.message().await? {
+                Some(resp) => resps.push(resp.try_into()?),
+                None => return Err(Error::KeepAliveLease),
+            }
+        }
+
+        Ok(resps)
+    }
+}
+
+/// MultiAutoKeepAlive is a handle of the background task started by `MultiKeepAlive::spawn`.
+pub struct MultiAutoKeepAlive {
+    client: Client,
+    state_rx: watch::Receiver<HashMap<LeaseId, Option<i64>>>,
+    task: JoinHandle<()>,
+}
+
+impl MultiAutoKeepAlive {
+    /// Returns the TTL granted by the server to the lease on the last refresh, or `None` before
+    /// the first one or if the lease is not kept alive by the task. A TTL of 0 means the lease has
+    /// expired or could not be refreshed before its TTL ran out.
+    pub fn ttl(&self, lease_id: LeaseId) -> Option<i64> {
+        self.state_rx.borrow().get(&lease_id).copied().flatten()
+    }
+
+    /// Returns the IDs of the leases which have expired or could not be refreshed in time.
+    pub fn expired(&self) -> Vec<LeaseId> {
+        let mut ids: Vec<LeaseId> = self
+            .state_rx
+            .borrow()
+            .iter()
+            .filter(|(_, ttl)| **ttl == Some(0))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Waits until the TTL of any lease changes, e.g. to notice when one expires.
+    /// Returns `Err(Error::ChannelClosed)` once the task has ended.
+    pub async fn changed(&mut self) -> Result<()> {
+        self.state_rx
+            .changed()
+            .await
+            .map_err(|_| Error::ChannelClosed)
+    }
+
+    /// Stops refreshing the leases. The leases themselves expire once their TTL elapses.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+
+    /// Stops refreshing the leases and revokes all of them, which deletes the keys attached to
+    /// them. Leases which no longer exist are skipped. Every lease is revoked even if revoking
+    /// another one fails, and the first error is returned.
+    pub async fn revoke(self) -> Result<()> {
+        self.task.abort();
+        let _ = self.task.await;
+
+        let ids: Vec<LeaseId> = self.state_rx.borrow().keys().copied().collect();
+        let mut result = Ok(());
+        for id in ids {
+            match self.client.revoke(LeaseRevokeRequest::new(id)).await {
+                Ok(_) | Err(Error::NotFound(_)) => {}
+                Err(e) => {
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                }
+            }
+        }
+
+        result
     }
 }
@@ -1,6 +1,6 @@
 use crate::lease::LeaseId;
 use crate::proto::etcdserverpb;
-use crate::ResponseHeader;
+use crate::{Error, ResponseHeader, Result};
 
 #[derive(Debug)]
 pub struct LeaseRevokeRequest {
@@ -26,10 +26,12 @@ pub struct LeaseRevokeResponse {
     pub header: ResponseHeader,
 }
 
-impl From<crate::proto::etcdserverpb::LeaseRevokeResponse> for LeaseRevokeResponse {
-    fn from(proto: crate::proto::etcdserverpb::LeaseRevokeResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
-        }
+impl TryFrom<crate::proto::etcdserverpb::LeaseRevokeResponse> for LeaseRevokeResponse {
+    type Error = Error;
+
+    fn try_from(proto: crate::proto::etcdserverpb::LeaseRevokeResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
     }
 }
@@ -1,5 +1,5 @@
 use crate::proto::etcdserverpb;
-use crate::{LeaseId, ResponseHeader};
+use crate::{Error, LeaseId, ResponseHeader, Result};
 
 #[derive(Debug)]
 pub struct LeaseTimeToLiveRequest {
@@ -20,6 +20,7 @@ impl LeaseTimeToLiveRequest {
         self
     }
 
+    /// When set, responds with the keys attached to the lease.
     pub fn with_keys(mut self, keys: bool) -> Self {
         self.proto.keys = keys;
         self
@@ -43,14 +44,22 @@ pub struct LeaseTimeToLiveResponse {
     pub header: ResponseHeader,
     pub id: LeaseId,
     pub ttl: i64,
+    /// The TTL the lease was initially granted with.
+    pub granted_ttl: i64,
+    /// The keys attached to the lease, only filled when the request was made with `with_keys`.
+    pub keys: Vec<Vec<u8>>,
 }
 
-impl From<crate::proto::etcdserverpb::LeaseTimeToLiveResponse> for LeaseTimeToLiveResponse {
-    fn from(proto: crate::proto::etcdserverpb::LeaseTimeToLiveResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl TryFrom<crate::proto::etcdserverpb::LeaseTimeToLiveResponse> for LeaseTimeToLiveResponse {
+    type Error = Error;
+
+    fn try_from(proto: crate::proto::etcdserverpb::LeaseTimeToLiveResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             id: proto.id,
             ttl: proto.ttl,
-        }
+            granted_ttl: proto.granted_ttl,
+            keys: proto.keys,
+        })
     }
 }
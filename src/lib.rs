@@ -4,41 +4,73 @@
 
 pub use auth::{
     AuthDisableResponse, AuthEnableResponse, AuthOp, AuthRoleAddRequest, AuthRoleAddResponse,
-    AuthRoleDeleteRequest, AuthRoleDeleteResponse, AuthRoleListResponse, AuthStatusRequest,
-    AuthStatusResponse, AuthenticateRequest, AuthenticateResponse,
+    AuthRoleDeleteRequest, AuthRoleDeleteResponse, AuthRoleGrantPermissionRequest,
+    AuthRoleGrantPermissionResponse, AuthRoleListResponse, AuthStatusRequest, AuthStatusResponse,
+    AuthenticateRequest, AuthenticateResponse, Permission,
 };
 pub use cluster::{
     ClusterOp, Member, MemberAddRequest, MemberAddResponse, MemberListRequest, MemberListResponse,
     MemberRemoveRequest, MemberRemoveResponse, MemberUpdateRequest, MemberUpdateResponse,
 };
+pub use dyn_op::{
+    DynAuthOp, DynClusterOp, DynKeyValueOp, DynLeaseOp, DynMaintenanceOp, DynWatchOp,
+};
+pub use election::{
+    CampaignRequest, CampaignResponse, ElectionOp, LeaderKey, LeaderRequest, LeaderResponse,
+    ProclaimRequest, ProclaimResponse, ResignRequest, ResignResponse,
+};
 pub use kv::{
-    CompactRequest, CompactResponse, DeleteRequest, DeleteResponse, KeyRange, KeyValue, KeyValueOp,
-    PutRequest, PutResponse, RangeRequest, RangeResponse, TxnCmp, TxnOp, TxnOpResponse, TxnRequest,
+    AutoCompactor, CompactRequest, CompactResponse, CompactRetention, DeleteCompaction,
+    DeleteRequest, DeleteResponse, Key, KeyRange, KeyValue, KeyValueOp, PutRequest, PutResponse,
+    RangeRequest, RangeResponse, SortOrder, TxnCmp, TxnCompare, TxnOp, TxnOpResponse, TxnRequest,
     TxnResponse,
 };
 pub use lease::{
-    LeaseGrantRequest, LeaseGrantResponse, LeaseId, LeaseKeepAlive, LeaseKeepAliveRequest,
-    LeaseKeepAliveResponse, LeaseOp, LeaseRevokeRequest, LeaseRevokeResponse,
-    LeaseTimeToLiveRequest, LeaseTimeToLiveResponse,
+    AutoKeepAlive, LeaseGrantRequest, LeaseGrantResponse, LeaseId, LeaseKeepAlive,
+    LeaseKeepAliveRequest, LeaseKeepAliveResponse, LeaseLeasesRequest, LeaseLeasesResponse,
+    LeaseOp, LeaseRegranted, LeaseRevokeRequest, LeaseRevokeResponse, LeaseStats,
+    LeaseTimeToLiveRequest, LeaseTimeToLiveResponse, MultiAutoKeepAlive, MultiKeepAlive,
+};
+pub use lock::{LockOp, LockOwner, LockRequest, LockResponse, UnlockRequest, UnlockResponse};
+pub use maintenance::{
+    verify_snapshot, verify_snapshot_file, ConsistencyReport, DowngradeRequest, DowngradeResponse,
+    Feature, HashKvRequest, HashKvResponse, MaintenanceOp, StatusRequest, StatusResponse,
 };
 pub use response_header::ResponseHeader;
+pub use session::{Session, SessionState};
 pub use watch::{
-    Event, EventType, WatchCancelRequest, WatchCanceler, WatchCreateRequest, WatchInbound, WatchOp,
-    WatchResponse, WatchStream,
+    CancelReason, CoalescedEvents, Event, EventBatch, EventType, HeartbeatItem, HeartbeatWatch,
+    KeyTransition, MembershipChange, MembershipChanges, PrefixWatchEvent, ReconcilingWatch,
+    ResumableWatch, WatchCancelRequest, WatchCanceler, WatchCreateRequest, WatchEvents,
+    WatchInbound, WatchOp, WatchResponse, WatchStream,
 };
 
-pub use client::{Client, ClientConfig, Endpoint};
+pub use bytes::Bytes;
+pub use circuit_breaker::CircuitBreakerConfig;
+pub use client::{Client, ClientConfig, Endpoint, LoadBalancing, ReadPreference};
 pub use error::Error;
+#[cfg(feature = "mock")]
+pub use mock::InMemoryKv;
+pub use tonic::codec::CompressionEncoding;
 
 mod auth;
+mod circuit_breaker;
 mod client;
 mod cluster;
+#[cfg(feature = "srv")]
+mod discovery;
+mod dyn_op;
+mod election;
 mod error;
 mod kv;
 mod lease;
 mod lock;
+mod maintenance;
+#[cfg(feature = "mock")]
+mod mock;
 mod proto;
 mod response_header;
+mod session;
 mod watch;
 
 pub type Result<T> = std::result::Result<T, Error>;
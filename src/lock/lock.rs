@@ -0,0 +1,90 @@
+use crate::proto::v3lockpb;
+use crate::{Error, LeaseId, ResponseHeader, Result};
+
+#[derive(Debug, Clone)]
+pub struct LockRequest {
+    proto: v3lockpb::LockRequest,
+}
+
+impl LockRequest {
+    /// Creates a new LockRequest for the named lock, held as long as the lease is alive.
+    pub fn new<N>(name: N, lease: LeaseId) -> Self
+    where
+        N: Into<Vec<u8>>,
+    {
+        Self {
+            proto: v3lockpb::LockRequest {
+                name: name.into(),
+                lease,
+            },
+        }
+    }
+}
+
+impl From<LockRequest> for v3lockpb::LockRequest {
+    fn from(req: LockRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LockResponse {
+    pub header: ResponseHeader,
+    /// The key owning the lock, which exists as long as the lock is held.
+    pub key: Vec<u8>,
+}
+
+impl LockResponse {
+    /// Returns the owner of the lock, whose fencing token protects external resources.
+    pub fn owner(&self) -> LockOwner {
+        LockOwner {
+            key: self.key.clone(),
+            revision: self.header.revision,
+        }
+    }
+}
+
+impl TryFrom<v3lockpb::LockResponse> for LockResponse {
+    type Error = Error;
+
+    fn try_from(proto: v3lockpb::LockResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            key: proto.key,
+        })
+    }
+}
+
+/// LockOwner is the key owning a lock, along with the revision the lock was acquired at.
+///
+/// A holder may lose the lock without noticing, e.g. when its lease expires during a long pause,
+/// while still acting on an external resource. To guard against this, the holder passes
+/// [`LockOwner::fencing_token`] along with every write to the resource, which rejects writes
+/// carrying a lower token than the highest it has seen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockOwner {
+    key: Vec<u8>,
+    revision: i64,
+}
+
+impl LockOwner {
+    /// Returns the key owning the lock, to be passed to `unlock`.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Returns the fencing token of the lock, which increases with every acquisition of the lock.
+    ///
+    /// The lock key, `<name>/<lease ID>`, does not embed a revision, so the token is the revision
+    /// the lock was acquired at. A later holder can only acquire the lock after the key of the
+    /// previous one is deleted, at a higher revision.
+    pub fn fencing_token(&self) -> i64 {
+        self.revision
+    }
+}
+
+impl From<LockOwner> for Vec<u8> {
+    fn from(owner: LockOwner) -> Self {
+        owner.key
+    }
+}
@@ -1,7 +1,23 @@
+//! The Lock API provides distributed shared locks, held as long as the lease they are attached to.
+
+mod lock;
+mod unlock;
+
+pub use lock::{LockOwner, LockRequest, LockResponse};
+pub use unlock::{UnlockRequest, UnlockResponse};
+
 use std::future::Future;
 
 use crate::Result;
 
 pub trait LockOp {
-    fn lock(&self) -> impl Future<Output = Result<()>>;
+    /// Waits until the lock is acquired, and returns the key owning it.
+    fn lock<R>(&self, req: R) -> impl Future<Output = Result<LockResponse>>
+    where
+        R: Into<LockRequest> + Send;
+
+    /// Releases the lock owned by the given key.
+    fn unlock<R>(&self, req: R) -> impl Future<Output = Result<UnlockResponse>>
+    where
+        R: Into<UnlockRequest> + Send;
 }
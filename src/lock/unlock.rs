@@ -0,0 +1,49 @@
+use crate::proto::v3lockpb;
+use crate::{Error, ResponseHeader, Result};
+
+#[derive(Debug, Clone)]
+pub struct UnlockRequest {
+    proto: v3lockpb::UnlockRequest,
+}
+
+impl UnlockRequest {
+    /// Creates a new UnlockRequest releasing the lock owned by the key.
+    pub fn new<K>(key: K) -> Self
+    where
+        K: Into<Vec<u8>>,
+    {
+        Self {
+            proto: v3lockpb::UnlockRequest { key: key.into() },
+        }
+    }
+}
+
+impl<K> From<K> for UnlockRequest
+where
+    K: Into<Vec<u8>>,
+{
+    fn from(key: K) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<UnlockRequest> for v3lockpb::UnlockRequest {
+    fn from(req: UnlockRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnlockResponse {
+    pub header: ResponseHeader,
+}
+
+impl TryFrom<v3lockpb::UnlockResponse> for UnlockResponse {
+    type Error = Error;
+
+    fn try_from(proto: v3lockpb::UnlockResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+        })
+    }
+}
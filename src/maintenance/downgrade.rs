@@ -0,0 +1,59 @@
+use crate::proto::etcdserverpb;
+use crate::proto::etcdserverpb::downgrade_request::DowngradeAction;
+use crate::{Error, ResponseHeader, Result};
+
+#[derive(Debug, Clone)]
+pub struct DowngradeRequest {
+    proto: etcdserverpb::DowngradeRequest,
+}
+
+impl DowngradeRequest {
+    fn new(action: DowngradeAction, version: String) -> Self {
+        Self {
+            proto: etcdserverpb::DowngradeRequest {
+                action: action as i32,
+                version,
+            },
+        }
+    }
+
+    /// Creates a new DowngradeRequest which checks whether the cluster can be downgraded to the
+    /// version, e.g. `3.4`.
+    pub fn validate(version: impl Into<String>) -> Self {
+        Self::new(DowngradeAction::Validate, version.into())
+    }
+
+    /// Creates a new DowngradeRequest which starts downgrading the cluster to the version.
+    pub fn enable(version: impl Into<String>) -> Self {
+        Self::new(DowngradeAction::Enable, version.into())
+    }
+
+    /// Creates a new DowngradeRequest which cancels the ongoing downgrade.
+    pub fn cancel() -> Self {
+        Self::new(DowngradeAction::Cancel, String::new())
+    }
+}
+
+impl From<DowngradeRequest> for etcdserverpb::DowngradeRequest {
+    fn from(req: DowngradeRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DowngradeResponse {
+    pub header: ResponseHeader,
+    /// The current cluster version.
+    pub version: String,
+}
+
+impl TryFrom<etcdserverpb::DowngradeResponse> for DowngradeResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::DowngradeResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            version: proto.version,
+        })
+    }
+}
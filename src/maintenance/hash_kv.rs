@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use crate::proto::etcdserverpb;
+use crate::{Error, ResponseHeader, Result};
+
+#[derive(Debug, Default, Clone)]
+pub struct HashKvRequest {
+    proto: etcdserverpb::HashKvRequest,
+}
+
+impl HashKvRequest {
+    /// Creates a new HashKvRequest which hashes the keys of the member up to the revision, or up
+    /// to its latest revision if it is 0.
+    pub fn new(revision: i64) -> Self {
+        Self {
+            proto: etcdserverpb::HashKvRequest { revision },
+        }
+    }
+}
+
+impl From<i64> for HashKvRequest {
+    fn from(revision: i64) -> Self {
+        Self::new(revision)
+    }
+}
+
+impl From<HashKvRequest> for etcdserverpb::HashKvRequest {
+    fn from(req: HashKvRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HashKvResponse {
+    pub header: ResponseHeader,
+    /// The hash of the MVCC keys of the responding member up to the requested revision.
+    pub hash: u32,
+    /// The compacted revision of the key-value store when the hash began.
+    pub compact_revision: i64,
+}
+
+impl TryFrom<etcdserverpb::HashKvResponse> for HashKvResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::HashKvResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            hash: proto.hash,
+            compact_revision: proto.compact_revision,
+        })
+    }
+}
+
+/// The keyspace hashes of the members of the cluster at one revision.
+#[derive(Debug, Clone)]
+pub struct ConsistencyReport {
+    /// The revision the keyspace was hashed at.
+    pub revision: i64,
+    /// The hash of each member by member ID.
+    pub hashes: HashMap<u64, u32>,
+    /// The IDs of the members whose hash differs from the one most members agree on.
+    pub divergent: Vec<u64>,
+}
+
+impl ConsistencyReport {
+    pub(crate) fn new(revision: i64, hashes: HashMap<u64, u32>) -> Self {
+        let mut counts: HashMap<u32, usize> = HashMap::new();
+        for hash in hashes.values() {
+            *counts.entry(*hash).or_default() += 1;
+        }
+
+        // Ties are broken by the hash itself so the report does not depend on iteration order.
+        let majority = counts
+            .into_iter()
+            .max_by_key(|(hash, count)| (*count, *hash))
+            .map(|(hash, _)| hash);
+
+        let mut divergent: Vec<u64> = hashes
+            .iter()
+            .filter(|(_, hash)| Some(**hash) != majority)
+            .map(|(id, _)| *id)
+            .collect();
+        divergent.sort_unstable();
+
+        Self {
+            revision,
+            hashes,
+            divergent,
+        }
+    }
+
+    /// Returns whether all the members have the same hash.
+    pub fn is_consistent(&self) -> bool {
+        self.divergent.is_empty()
+    }
+}
@@ -0,0 +1,31 @@
+//! The Maintenance API provides status and administration operations against individual etcd members.
+
+mod downgrade;
+mod hash_kv;
+mod snapshot;
+mod status;
+mod version;
+
+pub use downgrade::{DowngradeRequest, DowngradeResponse};
+pub use hash_kv::{ConsistencyReport, HashKvRequest, HashKvResponse};
+pub use snapshot::{verify_snapshot, verify_snapshot_file};
+pub use status::{StatusRequest, StatusResponse};
+pub use version::Feature;
+
+use std::future::Future;
+
+use crate::Result;
+
+pub trait MaintenanceOp {
+    fn status(&self) -> impl Future<Output = Result<StatusResponse>>;
+
+    /// Hashes the keys of the member serving the request up to the revision.
+    fn hash_kv<R>(&self, req: R) -> impl Future<Output = Result<HashKvResponse>>
+    where
+        R: Into<HashKvRequest> + Send;
+
+    /// Validates, enables or cancels a downgrade of the cluster version, from etcd 3.5.
+    fn downgrade<R>(&self, req: R) -> impl Future<Output = Result<DowngradeResponse>>
+    where
+        R: Into<DowngradeRequest> + Send;
+}
@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::{Error, Result};
+
+/// The size of the SHA256 checksum etcd appends to the snapshots it sends.
+const CHECKSUM_SIZE: usize = 32;
+
+/// The page size of the snapshot database, which the snapshot size is a multiple of without the
+/// checksum.
+const PAGE_SIZE: usize = 512;
+
+/// Verifies the integrity of a snapshot received from etcd, the way `etcdctl snapshot restore`
+/// does, before it is used to restore a cluster.
+///
+/// # Errors
+/// Will returns `Err(Error::SnapshotCorrupt)` if the snapshot is truncated or its trailing
+/// SHA256 checksum does not match its content.
+pub fn verify_snapshot(snapshot: &[u8]) -> Result<()> {
+    if snapshot.len() % PAGE_SIZE != CHECKSUM_SIZE {
+        return Err(Error::SnapshotCorrupt(format!(
+            "unexpected size {}, the snapshot is truncated or has no checksum",
+            snapshot.len()
+        )));
+    }
+
+    let (data, checksum) = snapshot.split_at(snapshot.len() - CHECKSUM_SIZE);
+    if Sha256::digest(data).as_slice() != checksum {
+        return Err(Error::SnapshotCorrupt("checksum mismatch".to_owned()));
+    }
+
+    Ok(())
+}
+
+/// Verifies the integrity of a snapshot saved to a file. See [`verify_snapshot`].
+pub fn verify_snapshot_file(path: impl AsRef<Path>) -> Result<()> {
+    let snapshot = std::fs::read(path)?;
+    verify_snapshot(&snapshot)
+}
@@ -0,0 +1,46 @@
+use crate::proto::etcdserverpb;
+use crate::{Error, ResponseHeader, Result};
+
+#[derive(Debug, Default, Clone)]
+pub struct StatusRequest {
+    proto: etcdserverpb::StatusRequest,
+}
+
+impl From<StatusRequest> for etcdserverpb::StatusRequest {
+    fn from(req: StatusRequest) -> Self {
+        req.proto
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusResponse {
+    pub header: ResponseHeader,
+    pub version: String,
+    pub db_size: i64,
+    pub leader: u64,
+    pub raft_index: u64,
+    pub raft_term: u64,
+    pub raft_applied_index: u64,
+    pub errors: Vec<String>,
+    pub db_size_in_use: i64,
+    pub is_learner: bool,
+}
+
+impl TryFrom<etcdserverpb::StatusResponse> for StatusResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::StatusResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
+            version: proto.version,
+            db_size: proto.db_size,
+            leader: proto.leader,
+            raft_index: proto.raft_index,
+            raft_term: proto.raft_term,
+            raft_applied_index: proto.raft_applied_index,
+            errors: proto.errors,
+            db_size_in_use: proto.db_size_in_use,
+            is_learner: proto.is_learner,
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// Feature is an optional feature of etcd, which is only available from some version on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// Listing all leases, from etcd 3.3.
+    LeaseLeases,
+    /// Requesting progress notifications on a watch stream, from etcd 3.4.
+    WatchProgressRequest,
+    /// Linearizable member lists, from etcd 3.5.
+    LinearizableMemberList,
+    /// Downgrading the cluster version, from etcd 3.5.
+    Downgrade,
+}
+
+impl Feature {
+    /// Returns the first `(major, minor)` etcd version supporting the feature.
+    pub fn min_version(self) -> (u32, u32) {
+        match self {
+            Feature::LeaseLeases => (3, 3),
+            Feature::WatchProgressRequest => (3, 4),
+            Feature::LinearizableMemberList | Feature::Downgrade => (3, 5),
+        }
+    }
+
+    /// Whether the given etcd version, e.g. `3.5.9`, supports the feature. Unparsable versions
+    /// are assumed to support it.
+    pub fn is_supported_by(self, version: &str) -> bool {
+        let mut parts = version.split('.').map(|part| part.parse::<u32>());
+        match (parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor))) => (major, minor) >= self.min_version(),
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (major, minor) = self.min_version();
+        write!(f, "{self:?} (etcd {major}.{minor}+)")
+    }
+}
@@ -0,0 +1,699 @@
+//! An in-memory implementation of the key-value API for testing code without an etcd cluster.
+//!
+//! `InMemoryKv` implements [`KeyValueOp`], so code written against `impl KeyValueOp` can be
+//! exercised against it. Only the latest revision of every key is kept: ranges at a past revision
+//! return the current state.
+
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    ops::Bound,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use futures::Stream;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::Status;
+
+use crate::proto::etcdserverpb::{
+    self,
+    compare::{CompareResult, CompareTarget, TargetUnion},
+    range_request::{SortOrder, SortTarget},
+    request_op::Request,
+    response_op::Response,
+};
+use crate::proto::mvccpb;
+use crate::{
+    CompactRequest, CompactResponse, DeleteRequest, DeleteResponse, Error, Event, KeyRange,
+    KeyValue, KeyValueOp, PutRequest, PutResponse, RangeRequest, RangeResponse, Result, TxnRequest,
+    TxnResponse,
+};
+
+const CLUSTER_ID: u64 = 1;
+const MEMBER_ID: u64 = 1;
+const RAFT_TERM: u64 = 1;
+
+/// InMemoryKv is a key-value store living in memory which mimics the behavior of etcd.
+#[derive(Clone, Default)]
+pub struct InMemoryKv {
+    store: Arc<Mutex<Store>>,
+}
+
+impl InMemoryKv {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to the changes of the given key range made from now on.
+    pub fn subscribe<R>(&self, key_range: R) -> Result<impl Stream<Item = Event>>
+    where
+        R: Into<KeyRange>,
+    {
+        let key_range = key_range.into();
+        let (tx, rx) = unbounded_channel();
+
+        self.lock()?.watchers.push(Watcher {
+            key: key_range.key,
+            range_end: key_range.range_end,
+            tx,
+        });
+
+        Ok(UnboundedReceiverStream::new(rx))
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Store>> {
+        self.store
+            .lock()
+            .map_err(|e| Error::PoisonError(e.to_string()))
+    }
+}
+
+impl KeyValueOp for InMemoryKv {
+    async fn put<R>(&self, req: R) -> Result<PutResponse>
+    where
+        R: Into<PutRequest>,
+    {
+        let req = req.into();
+        let with_current = req.is_with_current();
+        let key = req.key().to_vec();
+
+        let mut store = self.lock()?;
+        let revision = store.revision + 1;
+        let mut events = vec![];
+
+        let mut resp = store.put(req.into(), revision, &mut events)?;
+        store.commit(revision, events);
+        resp.header = Some(store.header());
+
+        let mut resp: PutResponse = resp.try_into()?;
+        if with_current {
+            resp.current = store.kvs.get(&key).cloned().map(KeyValue::from);
+        }
+
+        Ok(resp)
+    }
+
+    async fn get<R>(&self, req: R) -> Result<RangeResponse>
+    where
+        R: Into<RangeRequest>,
+    {
+        let store = self.lock()?;
+
+        let mut resp = store.range(req.into().into())?;
+        resp.header = Some(store.header());
+
+        resp.try_into()
+    }
+
+    async fn get_all(&self) -> Result<RangeResponse> {
+        self.get(KeyRange::all()).await
+    }
+
+    async fn get_by_prefix<K>(&self, p: K) -> Result<RangeResponse>
+    where
+        K: Into<Vec<u8>>,
+    {
+        self.get(KeyRange::prefix(p)).await
+    }
+
+    async fn get_range<F, E>(&self, from: F, end: E) -> Result<RangeResponse>
+    where
+        F: Into<Vec<u8>>,
+        E: Into<Vec<u8>>,
+    {
+        self.get(KeyRange::range(from, end)).await
+    }
+
+    async fn delete<R>(&self, req: R) -> Result<DeleteResponse>
+    where
+        R: Into<DeleteRequest>,
+    {
+        let mut store = self.lock()?;
+        let revision = store.revision + 1;
+        let mut events = vec![];
+
+        let mut resp = store.delete(req.into().into(), revision, &mut events);
+        store.commit(revision, events);
+        resp.header = Some(store.header());
+
+        resp.try_into()
+    }
+
+    async fn delete_all(&self) -> Result<DeleteResponse> {
+        self.delete(KeyRange::all()).await
+    }
+
+    async fn delete_by_prefix<K>(&self, p: K) -> Result<DeleteResponse>
+    where
+        K: Into<Vec<u8>>,
+    {
+        self.delete(KeyRange::prefix(p)).await
+    }
+
+    async fn delete_range<F, E>(&self, from: F, end: E) -> Result<DeleteResponse>
+    where
+        F: Into<Vec<u8>>,
+        E: Into<Vec<u8>>,
+    {
+        self.delete(KeyRange::range(from, end)).await
+    }
+
+    async fn txn<R>(&self, req: R) -> Result<TxnResponse>
+    where
+        R: Into<TxnRequest>,
+    {
+        let mut store = self.lock()?;
+        let revision = store.revision + 1;
+        let mut events = vec![];
+
+        // Like etcd, a transaction whose op fails changes nothing, so the key-values changed by
+        // the ops before the failing one are restored.
+        let kvs = store.kvs.clone();
+        let mut resp = match store.txn(req.into().into(), revision, &mut events) {
+            Ok(resp) => resp,
+            Err(e) => {
+                store.kvs = kvs;
+                return Err(e);
+            }
+        };
+        store.commit(revision, events);
+        fill_txn_headers(&mut resp, &store.header());
+
+        resp.try_into()
+    }
+
+    async fn compact<R>(&self, req: R) -> Result<CompactResponse>
+    where
+        R: Into<CompactRequest>,
+    {
+        let mut store = self.lock()?;
+        let req: etcdserverpb::CompactionRequest = req.into().into();
+
+        store.check_revision(req.revision)?;
+        store.compact_revision = req.revision;
+
+        etcdserverpb::CompactionResponse {
+            header: Some(store.header()),
+        }
+        .try_into()
+    }
+}
+
+struct Watcher {
+    key: Vec<u8>,
+    range_end: Vec<u8>,
+    tx: UnboundedSender<Event>,
+}
+
+struct Store {
+    revision: i64,
+    compact_revision: i64,
+    kvs: BTreeMap<Vec<u8>, mvccpb::KeyValue>,
+    watchers: Vec<Watcher>,
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self {
+            // etcd starts with revision 1 on an empty store.
+            revision: 1,
+            compact_revision: 0,
+            kvs: BTreeMap::new(),
+            watchers: vec![],
+        }
+    }
+}
+
+impl Store {
+    fn header(&self) -> etcdserverpb::ResponseHeader {
+        etcdserverpb::ResponseHeader {
+            cluster_id: CLUSTER_ID,
+            member_id: MEMBER_ID,
+            revision: self.revision,
+            raft_term: RAFT_TERM,
+        }
+    }
+
+    fn check_revision(&self, revision: i64) -> Result<()> {
+        if revision > self.revision {
+            return Err(Error::FutureRevision {
+                requested: revision,
+                current: self.revision,
+            });
+        }
+        if revision > 0 && revision <= self.compact_revision {
+            return Err(Status::out_of_range(
+                "etcdserver: mvcc: required revision has been compacted",
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    fn select(&self, key: &[u8], range_end: &[u8]) -> Vec<&mvccpb::KeyValue> {
+        if range_end.is_empty() {
+            return self.kvs.get(key).into_iter().collect();
+        }
+
+        let end = if range_end == [0] {
+            Bound::Unbounded
+        } else if range_end <= key {
+            return vec![];
+        } else {
+            Bound::Excluded(range_end)
+        };
+        self.kvs
+            .range::<[u8], _>((Bound::Included(key), end))
+            .map(|(_, kv)| kv)
+            .collect()
+    }
+
+    fn commit(&mut self, revision: i64, events: Vec<mvccpb::Event>) {
+        if events.is_empty() {
+            return;
+        }
+        self.revision = revision;
+
+        for event in events {
+            // The events of the store always carry their key-value.
+            let Ok(event) = Event::try_from(event) else {
+                continue;
+            };
+            let key = &event.kv.key;
+            self.watchers.retain(|w| {
+                !in_range(key, &w.key, &w.range_end) || w.tx.send(event.clone()).is_ok()
+            });
+        }
+    }
+
+    fn range(&self, req: etcdserverpb::RangeRequest) -> Result<etcdserverpb::RangeResponse> {
+        self.check_revision(req.revision)?;
+
+        let mut kvs: Vec<mvccpb::KeyValue> = self
+            .select(&req.key, &req.range_end)
+            .into_iter()
+            .filter(|kv| {
+                (req.min_mod_revision == 0 || kv.mod_revision >= req.min_mod_revision)
+                    && (req.max_mod_revision == 0 || kv.mod_revision <= req.max_mod_revision)
+                    && (req.min_create_revision == 0
+                        || kv.create_revision >= req.min_create_revision)
+                    && (req.max_create_revision == 0
+                        || kv.create_revision <= req.max_create_revision)
+            })
+            .cloned()
+            .collect();
+
+        let target = SortTarget::try_from(req.sort_target).unwrap_or(SortTarget::Key);
+        let order = match SortOrder::try_from(req.sort_order).unwrap_or(SortOrder::None) {
+            // Like etcd, sorting by a target other than the key defaults to ascending order.
+            SortOrder::None if target != SortTarget::Key => SortOrder::Ascend,
+            order => order,
+        };
+        if order != SortOrder::None {
+            kvs.sort_by(|a, b| {
+                let ord = match target {
+                    SortTarget::Key => a.key.cmp(&b.key),
+                    SortTarget::Version => a.version.cmp(&b.version),
+                    SortTarget::Create => a.create_revision.cmp(&b.create_revision),
+                    SortTarget::Mod => a.mod_revision.cmp(&b.mod_revision),
+                    SortTarget::Value => a.value.cmp(&b.value),
+                };
+                if order == SortOrder::Descend {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            });
+        }
+
+        let count = kvs.len() as i64;
+        let more = req.limit > 0 && count > req.limit;
+        if more {
+            kvs.truncate(req.limit as usize);
+        }
+        if req.keys_only {
+            kvs.iter_mut().for_each(|kv| kv.value.clear());
+        }
+        if req.count_only {
+            kvs.clear();
+        }
+
+        Ok(etcdserverpb::RangeResponse {
+            header: None,
+            kvs,
+            more,
+            count,
+        })
+    }
+
+    fn put(
+        &mut self,
+        req: etcdserverpb::PutRequest,
+        revision: i64,
+        events: &mut Vec<mvccpb::Event>,
+    ) -> Result<etcdserverpb::PutResponse> {
+        let prev = self.kvs.get(&req.key).cloned();
+
+        let kv = match &prev {
+            None if req.ignore_value || req.ignore_lease => {
+                return Err(Status::invalid_argument("etcdserver: key not found").into());
+            }
+            None => mvccpb::KeyValue {
+                key: req.key,
+                create_revision: revision,
+                mod_revision: revision,
+                version: 1,
+                value: req.value,
+                lease: req.lease,
+            },
+            Some(prev) => mvccpb::KeyValue {
+                key: req.key,
+                create_revision: prev.create_revision,
+                mod_revision: revision,
+                version: prev.version + 1,
+                value: if req.ignore_value {
+                    prev.value.clone()
+                } else {
+                    req.value
+                },
+                lease: if req.ignore_lease {
+                    prev.lease
+                } else {
+                    req.lease
+                },
+            },
+        };
+
+        self.kvs.insert(kv.key.clone(), kv.clone());
+        events.push(mvccpb::Event {
+            r#type: mvccpb::event::EventType::Put as i32,
+            kv: Some(kv),
+            prev_kv: prev.clone(),
+        });
+
+        Ok(etcdserverpb::PutResponse {
+            header: None,
+            prev_kv: prev.filter(|_| req.prev_kv),
+        })
+    }
+
+    fn delete(
+        &mut self,
+        req: etcdserverpb::DeleteRangeRequest,
+        revision: i64,
+        events: &mut Vec<mvccpb::Event>,
+    ) -> etcdserverpb::DeleteRangeResponse {
+        let keys: Vec<Vec<u8>> = self
+            .select(&req.key, &req.range_end)
+            .into_iter()
+            .map(|kv| kv.key.clone())
+            .collect();
+
+        let mut prev_kvs = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(prev) = self.kvs.remove(&key) {
+                events.push(mvccpb::Event {
+                    r#type: mvccpb::event::EventType::Delete as i32,
+                    kv: Some(mvccpb::KeyValue {
+                        key,
+                        mod_revision: revision,
+                        ..Default::default()
+                    }),
+                    prev_kv: Some(prev.clone()),
+                });
+                prev_kvs.push(prev);
+            }
+        }
+
+        etcdserverpb::DeleteRangeResponse {
+            header: None,
+            deleted: prev_kvs.len() as i64,
+            prev_kvs: if req.prev_kv { prev_kvs } else { vec![] },
+        }
+    }
+
+    fn txn(
+        &mut self,
+        req: etcdserverpb::TxnRequest,
+        revision: i64,
+        events: &mut Vec<mvccpb::Event>,
+    ) -> Result<etcdserverpb::TxnResponse> {
+        let succeeded = req.compare.iter().all(|cmp| self.compare(cmp));
+        let ops = if succeeded { req.success } else { req.failure };
+
+        let mut responses = Vec::with_capacity(ops.len());
+        for op in ops {
+            let resp = match op.request {
+                Some(Request::RequestRange(req)) => Response::ResponseRange(self.range(req)?),
+                Some(Request::RequestPut(req)) => {
+                    Response::ResponsePut(self.put(req, revision, events)?)
+                }
+                Some(Request::RequestDeleteRange(req)) => {
+                    Response::ResponseDeleteRange(self.delete(req, revision, events))
+                }
+                Some(Request::RequestTxn(req)) => {
+                    Response::ResponseTxn(self.txn(req, revision, events)?)
+                }
+                None => continue,
+            };
+            responses.push(etcdserverpb::ResponseOp {
+                response: Some(resp),
+            });
+        }
+
+        Ok(etcdserverpb::TxnResponse {
+            header: None,
+            succeeded,
+            responses,
+        })
+    }
+
+    fn compare(&self, cmp: &etcdserverpb::Compare) -> bool {
+        let kvs = self.select(&cmp.key, &cmp.range_end);
+
+        // Like etcd, a missing key compares as a key-value of zero revisions, except on its value.
+        if kvs.is_empty() {
+            return cmp.target != CompareTarget::Value as i32
+                && compare_kv(cmp, &mvccpb::KeyValue::default());
+        }
+        kvs.into_iter().all(|kv| compare_kv(cmp, kv))
+    }
+}
+
+fn compare_kv(cmp: &etcdserverpb::Compare, kv: &mvccpb::KeyValue) -> bool {
+    let ord = match &cmp.target_union {
+        Some(TargetUnion::Version(v)) => kv.version.cmp(v),
+        Some(TargetUnion::CreateRevision(v)) => kv.create_revision.cmp(v),
+        Some(TargetUnion::ModRevision(v)) => kv.mod_revision.cmp(v),
+        Some(TargetUnion::Value(v)) => kv.value[..].cmp(v),
+        Some(TargetUnion::Lease(v)) => kv.lease.cmp(v),
+        None => return false,
+    };
+
+    match CompareResult::try_from(cmp.result) {
+        Ok(CompareResult::Equal) => ord == Ordering::Equal,
+        Ok(CompareResult::NotEqual) => ord != Ordering::Equal,
+        Ok(CompareResult::Greater) => ord == Ordering::Greater,
+        Ok(CompareResult::Less) => ord == Ordering::Less,
+        Err(_) => false,
+    }
+}
+
+fn fill_txn_headers(resp: &mut etcdserverpb::TxnResponse, header: &etcdserverpb::ResponseHeader) {
+    resp.header = Some(*header);
+
+    for op in resp.responses.iter_mut() {
+        match &mut op.response {
+            Some(Response::ResponseRange(r)) => r.header = Some(*header),
+            Some(Response::ResponsePut(r)) => r.header = Some(*header),
+            Some(Response::ResponseDeleteRange(r)) => r.header = Some(*header),
+            Some(Response::ResponseTxn(r)) => fill_txn_headers(r, header),
+            None => {}
+        }
+    }
+}
+
+fn in_range(key: &[u8], start: &[u8], range_end: &[u8]) -> bool {
+    if range_end.is_empty() {
+        key == start
+    } else if range_end == [0] {
+        key >= start
+    } else {
+        key >= start && key < range_end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompactRequest, TxnCmp, TxnCompare};
+
+    #[tokio::test]
+    async fn put_and_range() {
+        let kv = InMemoryKv::new();
+        kv.put(("a", "1")).await.unwrap();
+        kv.put(("b", "2")).await.unwrap();
+        kv.put(("c", "3")).await.unwrap();
+
+        let resp = kv.get("b").await.unwrap();
+        assert_eq!(resp.kvs.len(), 1);
+        assert_eq!(resp.kvs[0].value_str(), "2");
+
+        let resp = kv.get_range("a", "c").await.unwrap();
+        let keys: Vec<_> = resp.kvs.iter().map(|kv| kv.key_str()).collect();
+        assert_eq!(keys, ["a", "b"]);
+
+        let resp = kv
+            .get(RangeRequest::new(KeyRange::all()).limit(2))
+            .await
+            .unwrap();
+        assert_eq!(resp.kvs.len(), 2);
+        assert_eq!(resp.count, 3);
+        assert!(resp.has_more);
+    }
+
+    #[tokio::test]
+    async fn put_updates_revisions_and_version() {
+        let kv = InMemoryKv::new();
+        let first = kv.put(("a", "1")).await.unwrap().header.revision();
+        let second = kv.put(("a", "2")).await.unwrap().header.revision();
+        assert_eq!(second, first + 1);
+
+        let resp = kv.get("a").await.unwrap();
+        assert_eq!(resp.header.revision(), second);
+        assert_eq!(resp.kvs[0].create_revision, first);
+        assert_eq!(resp.kvs[0].mod_revision, second);
+        assert_eq!(resp.kvs[0].version, 2);
+    }
+
+    #[tokio::test]
+    async fn put_with_prev_kv() {
+        let kv = InMemoryKv::new();
+        kv.put(("a", "1")).await.unwrap();
+
+        let resp = kv
+            .put(PutRequest::new("a", "2").prev_kv(true))
+            .await
+            .unwrap();
+        assert_eq!(resp.prev_kv.value_str(), "1");
+    }
+
+    #[tokio::test]
+    async fn delete_range() {
+        let kv = InMemoryKv::new();
+        kv.put(("a/1", "1")).await.unwrap();
+        kv.put(("a/2", "2")).await.unwrap();
+        kv.put(("b", "3")).await.unwrap();
+
+        let resp = kv
+            .delete(DeleteRequest::new(KeyRange::prefix("a/")).with_prev_kv())
+            .await
+            .unwrap();
+        assert_eq!(resp.deleted, 2);
+        assert_eq!(resp.prev_kvs.len(), 2);
+
+        let resp = kv.get_all().await.unwrap();
+        assert_eq!(resp.kvs.len(), 1);
+        assert_eq!(resp.kvs[0].key_str(), "b");
+    }
+
+    #[tokio::test]
+    async fn delete_of_missing_key_keeps_revision() {
+        let kv = InMemoryKv::new();
+        let revision = kv.put(("a", "1")).await.unwrap().header.revision();
+
+        let resp = kv.delete(DeleteRequest::new("b")).await.unwrap();
+        assert_eq!(resp.deleted, 0);
+        assert_eq!(resp.header.revision(), revision);
+    }
+
+    #[tokio::test]
+    async fn txn_takes_branch_of_compare() {
+        let kv = InMemoryKv::new();
+        kv.put(("a", "1")).await.unwrap();
+
+        let txn = || {
+            TxnRequest::new()
+                .when(TxnCompare::value(KeyRange::key("a"), TxnCmp::Equal, "1"))
+                .and_then(PutRequest::new("a", "2"))
+                .or_else(RangeRequest::new(KeyRange::key("a")))
+        };
+
+        let resp = kv.txn(txn()).await.unwrap();
+        assert!(resp.succeeded);
+        assert_eq!(kv.get("a").await.unwrap().kvs[0].value_str(), "2");
+
+        let resp = kv.txn(txn()).await.unwrap();
+        assert!(!resp.succeeded);
+        assert_eq!(resp.range_kvs().next().unwrap().value_str(), "2");
+    }
+
+    #[tokio::test]
+    async fn txn_ops_share_one_revision() {
+        let kv = InMemoryKv::new();
+        kv.txn(
+            TxnRequest::new()
+                .and_then(PutRequest::new("a", "1"))
+                .and_then(PutRequest::new("b", "2")),
+        )
+        .await
+        .unwrap();
+
+        let resp = kv.get_all().await.unwrap();
+        assert!(resp
+            .kvs
+            .iter()
+            .all(|kv| kv.mod_revision == resp.header.revision()));
+    }
+
+    #[tokio::test]
+    async fn failed_txn_changes_nothing() {
+        let kv = InMemoryKv::new();
+        let revision = kv.put(("a", "1")).await.unwrap().header.revision();
+
+        // Ignoring the value of a missing key fails after the first put is applied.
+        let result = kv
+            .txn(
+                TxnRequest::new()
+                    .and_then(PutRequest::new("a", "2"))
+                    .and_then(PutRequest::new("missing", "").ignore_value()),
+            )
+            .await;
+        assert!(result.is_err());
+
+        let resp = kv.get("a").await.unwrap();
+        assert_eq!(resp.kvs[0].value_str(), "1");
+        assert_eq!(resp.header.revision(), revision);
+    }
+
+    #[tokio::test]
+    async fn range_at_future_revision_fails() {
+        let kv = InMemoryKv::new();
+        let revision = kv.put(("a", "1")).await.unwrap().header.revision();
+
+        let result = kv
+            .get(RangeRequest::new(KeyRange::key("a")).revision(revision + 1))
+            .await;
+        assert!(matches!(result, Err(Error::FutureRevision { .. })));
+    }
+
+    #[tokio::test]
+    async fn compacted_revisions_are_unavailable() {
+        let kv = InMemoryKv::new();
+        let first = kv.put(("a", "1")).await.unwrap().header.revision();
+        let second = kv.put(("a", "2")).await.unwrap().header.revision();
+
+        kv.compact(CompactRequest::new(first)).await.unwrap();
+
+        let result = kv
+            .get(RangeRequest::new(KeyRange::key("a")).revision(first))
+            .await;
+        assert!(result.is_err());
+        kv.get(RangeRequest::new(KeyRange::key("a")).revision(second))
+            .await
+            .unwrap();
+        assert!(kv.compact(CompactRequest::new(second + 1)).await.is_err());
+    }
+}
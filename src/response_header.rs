@@ -1,34 +1,134 @@
 use crate::proto::etcdserverpb;
+use crate::{Error, Result};
 
 #[derive(Debug, Clone)]
 pub struct ResponseHeader {
-    proto: crate::proto::etcdserverpb::ResponseHeader,
+    /// The ID of the cluster which sent the response.
+    pub cluster_id: u64,
+    /// The ID of the member which sent the response.
+    pub member_id: u64,
+    /// The key-value store revision when the request was applied.
+    pub revision: i64,
+    /// The raft term when the request was applied.
+    pub raft_term: u64,
 }
 
 impl ResponseHeader {
     /// Get the ID of the cluster which sent the response.
     pub fn cluster_id(&self) -> u64 {
-        self.proto.cluster_id
+        self.cluster_id
     }
 
     /// Get the ID of the member which sent the response.
     pub fn member_id(&self) -> u64 {
-        self.proto.member_id
+        self.member_id
     }
 
     /// Get the key-value store revision when the request was applied.
     pub fn revision(&self) -> i64 {
-        self.proto.revision
+        self.revision
     }
 
     /// Get the raft term when the request was applied.
     pub fn raft_term(&self) -> u64 {
-        self.proto.raft_term
+        self.raft_term
+    }
+
+    /// Converts the header of a response, which etcd always sets.
+    pub(crate) fn required(header: Option<etcdserverpb::ResponseHeader>) -> Result<Self> {
+        header.map(From::from).ok_or(Error::MissingHeader)
     }
 }
 
 impl From<etcdserverpb::ResponseHeader> for ResponseHeader {
     fn from(proto: etcdserverpb::ResponseHeader) -> Self {
-        Self { proto }
+        Self {
+            cluster_id: proto.cluster_id,
+            member_id: proto.member_id,
+            revision: proto.revision,
+            raft_term: proto.raft_term,
+        }
+    }
+}
+
+macro_rules! impl_header_accessors {
+    ($($response:ty),* $(,)?) => {
+        $(
+            impl $response {
+                /// Get the key-value store revision when the request was applied.
+                pub fn revision(&self) -> i64 {
+                    self.header.revision
+                }
+
+                /// Get the raft term when the request was applied.
+                pub fn raft_term(&self) -> u64 {
+                    self.header.raft_term
+                }
+            }
+        )*
+    };
+}
+
+impl_header_accessors!(
+    crate::AuthDisableResponse,
+    crate::AuthEnableResponse,
+    crate::AuthRoleAddResponse,
+    crate::AuthRoleDeleteResponse,
+    crate::AuthRoleGrantPermissionResponse,
+    crate::AuthRoleListResponse,
+    crate::AuthStatusResponse,
+    crate::AuthenticateResponse,
+    crate::CampaignResponse,
+    crate::CompactResponse,
+    crate::DeleteResponse,
+    crate::DowngradeResponse,
+    crate::HashKvResponse,
+    crate::LeaseGrantResponse,
+    crate::LeaseKeepAliveResponse,
+    crate::LeaseLeasesResponse,
+    crate::LeaseRevokeResponse,
+    crate::LeaderResponse,
+    crate::LeaseTimeToLiveResponse,
+    crate::LockResponse,
+    crate::MemberAddResponse,
+    crate::MemberListResponse,
+    crate::MemberRemoveResponse,
+    crate::MemberUpdateResponse,
+    crate::ProclaimResponse,
+    crate::PutResponse,
+    crate::RangeResponse,
+    crate::ResignResponse,
+    crate::StatusResponse,
+    crate::TxnResponse,
+    crate::UnlockResponse,
+    crate::WatchResponse,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_keeps_all_fields() {
+        let header = ResponseHeader::required(Some(etcdserverpb::ResponseHeader {
+            cluster_id: 14841639068965178418,
+            member_id: 10276657743932975437,
+            revision: 42,
+            raft_term: 3,
+        }))
+        .unwrap();
+
+        assert_eq!(header.cluster_id(), 14841639068965178418);
+        assert_eq!(header.member_id(), 10276657743932975437);
+        assert_eq!(header.revision(), 42);
+        assert_eq!(header.raft_term(), 3);
+    }
+
+    #[test]
+    fn missing_header_is_an_error() {
+        assert!(matches!(
+            ResponseHeader::required(None),
+            Err(Error::MissingHeader)
+        ));
     }
 }
@@ -0,0 +1,124 @@
+//! Sessions tie locks and elections to a lease which is kept alive in the background.
+
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{
+    AutoKeepAlive, CampaignRequest, CampaignResponse, Client, ElectionOp, Error, LeaderKey,
+    LeaseId, LockOp, LockRequest, LockResponse, Result,
+};
+
+/// SessionState is what identifies a session, so that it can be handed off to another process,
+/// e.g. a standby taking over, and resumed there with `Client::resume_session`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionState {
+    /// The lease of the session.
+    pub lease_id: LeaseId,
+    /// The leadership won by the session, if any.
+    pub leader: Option<LeaderKey>,
+    /// Whether the lease of the session was lost, in which case its locks and leadership are
+    /// released and the session cannot be resumed.
+    pub expired: bool,
+}
+
+/// Session is a lease kept alive in the background, to which locks and campaigns are attached.
+///
+/// Closing the session revokes the lease, which releases all of them at once. If the session is
+/// lost because the lease could not be refreshed in time, they are released as well. A broken
+/// keep-alive stream is reopened in the background, so only the expiry of the lease loses the
+/// session, which is reported by `expired` and `SessionState::expired`.
+pub struct Session {
+    client: Client,
+    keep_alive: AutoKeepAlive,
+    leader: Mutex<Option<LeaderKey>>,
+}
+
+impl Session {
+    pub(crate) fn new(
+        client: Client,
+        keep_alive: AutoKeepAlive,
+        leader: Option<LeaderKey>,
+    ) -> Self {
+        Self {
+            client,
+            keep_alive,
+            leader: Mutex::new(leader),
+        }
+    }
+
+    /// Returns the ID of the lease of the session.
+    #[inline]
+    pub fn lease_id(&self) -> LeaseId {
+        self.keep_alive.lease_id()
+    }
+
+    /// Whether the lease of the session is still known to be alive.
+    pub fn is_alive(&self) -> bool {
+        self.keep_alive.ttl() != Some(0)
+    }
+
+    /// Waits until the lease of the session is lost, i.e. it expired or could not be refreshed
+    /// before its TTL ran out.
+    pub async fn expired(&self) {
+        self.keep_alive.expired().await
+    }
+
+    /// Returns the leadership last won by the session with `campaign`.
+    pub fn leader(&self) -> Result<Option<LeaderKey>> {
+        Ok(self.lock_leader()?.clone())
+    }
+
+    /// Returns the state identifying the session, to resume it in another process.
+    pub fn state(&self) -> Result<SessionState> {
+        Ok(SessionState {
+            lease_id: self.lease_id(),
+            leader: self.leader()?,
+            expired: !self.is_alive(),
+        })
+    }
+
+    /// Stops refreshing the lease without revoking it, and returns the state of the session for
+    /// another process to resume it with `Client::resume_session` before the lease expires.
+    pub fn detach(self) -> Result<SessionState> {
+        let state = self.state()?;
+        self.keep_alive.stop();
+
+        Ok(state)
+    }
+
+    /// Waits until the named lock is acquired on behalf of the session.
+    pub async fn lock<N>(&self, name: N) -> Result<LockResponse>
+    where
+        N: Into<Vec<u8>>,
+    {
+        self.client
+            .lock(LockRequest::new(name, self.lease_id()))
+            .await
+    }
+
+    /// Waits until the session is elected leader of the election.
+    pub async fn campaign<N, V>(&self, name: N, value: V) -> Result<CampaignResponse>
+    where
+        N: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let resp = self
+            .client
+            .campaign(CampaignRequest::new(name, self.lease_id(), value))
+            .await?;
+        *self.lock_leader()? = Some(resp.leader.clone());
+
+        Ok(resp)
+    }
+
+    /// Stops refreshing the lease and revokes it, releasing the locks and leaderships of the
+    /// session.
+    pub async fn close(self) -> Result<()> {
+        self.keep_alive.revoke().await
+    }
+
+    fn lock_leader(&self) -> Result<MutexGuard<'_, Option<LeaderKey>>> {
+        self.leader
+            .lock()
+            .map_err(|e| Error::PoisonError(e.to_string()))
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::Stream;
+use tokio::time::Sleep;
+
+use super::{Event, WatchEvents};
+use crate::{Error, Result};
+
+/// CoalescedEvents yields only the latest event of each key changed within a debounce window.
+///
+/// The window starts with the first event received after the previous flush. Events of
+/// different keys are yielded in the order their keys first changed within the window.
+pub struct CoalescedEvents {
+    events: WatchEvents,
+    debounce: Duration,
+    deadline: Option<Pin<Box<Sleep>>>,
+    pending: Vec<Event>,
+    index: HashMap<Vec<u8>, usize>,
+    ready: VecDeque<Event>,
+    is_closed: bool,
+}
+
+impl CoalescedEvents {
+    pub(crate) fn new(events: WatchEvents, debounce: Duration) -> Self {
+        Self {
+            events,
+            debounce,
+            deadline: None,
+            pending: Vec::new(),
+            index: HashMap::new(),
+            ready: VecDeque::new(),
+            is_closed: false,
+        }
+    }
+
+    pub async fn cancel(self) -> Result<()> {
+        self.events.cancel().await
+    }
+
+    /// Returns why the stream ended, see `WatchEvents::error`.
+    pub fn error(&self) -> Option<&Error> {
+        self.events.error()
+    }
+
+    fn push(&mut self, event: Event) {
+        match self.index.get(&event.kv.key) {
+            Some(&i) => self.pending[i] = event,
+            None => {
+                self.index.insert(event.kv.key.clone(), self.pending.len());
+                self.pending.push(event);
+            }
+        }
+
+        if self.deadline.is_none() {
+            self.deadline = Some(Box::pin(tokio::time::sleep(self.debounce)));
+        }
+    }
+
+    fn flush(&mut self) {
+        self.index.clear();
+        self.ready.extend(self.pending.drain(..));
+        self.deadline = None;
+    }
+}
+
+impl Stream for CoalescedEvents {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(event) = this.ready.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+
+            while !this.is_closed {
+                match Pin::new(&mut this.events).poll_next(cx) {
+                    Poll::Ready(Some(event)) => this.push(event),
+                    Poll::Ready(None) => this.is_closed = true,
+                    Poll::Pending => break,
+                }
+            }
+
+            if this.is_closed {
+                if this.pending.is_empty() {
+                    return Poll::Ready(None);
+                }
+                this.flush();
+                continue;
+            }
+
+            let expired = match this.deadline.as_mut() {
+                Some(deadline) => deadline.as_mut().poll(cx).is_ready(),
+                None => false,
+            };
+            if !expired {
+                return Poll::Pending;
+            }
+            this.flush();
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::Stream;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+use super::{WatchInbound, WatchStream};
+
+/// An item of a [`HeartbeatWatch`].
+#[derive(Debug)]
+pub enum HeartbeatItem {
+    Inbound(WatchInbound),
+    /// Nothing was received from the watch over a whole interval.
+    Heartbeat,
+}
+
+/// HeartbeatWatch yields the items of a watch stream, and a heartbeat whenever nothing is
+/// received over the interval, so that a consumer loop never waits longer than the interval.
+///
+/// Unlike progress notifications, heartbeats are generated locally and say nothing about the
+/// revision the watch has reached.
+pub struct HeartbeatWatch {
+    stream: WatchStream,
+    interval: Interval,
+}
+
+impl HeartbeatWatch {
+    pub(crate) fn new(stream: WatchStream, period: Duration) -> Self {
+        let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        Self { stream, interval }
+    }
+
+    /// Returns the underlying watch stream.
+    pub fn into_inner(self) -> WatchStream {
+        self.stream
+    }
+}
+
+impl Stream for HeartbeatWatch {
+    type Item = HeartbeatItem;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(inbound) = Pin::new(&mut this.stream).poll_next(cx) {
+            this.interval.reset();
+            return Poll::Ready(inbound.map(HeartbeatItem::Inbound));
+        }
+
+        match this.interval.poll_tick(cx) {
+            Poll::Ready(_) => Poll::Ready(Some(HeartbeatItem::Heartbeat)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    collections::HashSet,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+
+use super::{EventType, WatchEvents};
+use crate::{Error, Result};
+
+/// A change of the set of keys being watched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MembershipChange {
+    Added(Vec<u8>),
+    Removed(Vec<u8>),
+}
+
+/// MembershipChanges yields the additions and removals of keys, ignoring updates of the values of
+/// existing keys.
+pub struct MembershipChanges {
+    events: WatchEvents,
+    keys: HashSet<Vec<u8>>,
+}
+
+impl MembershipChanges {
+    pub(crate) fn new<I>(events: WatchEvents, keys: I) -> Self
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        Self {
+            events,
+            keys: keys.into_iter().collect(),
+        }
+    }
+
+    /// Returns the keys currently known to exist.
+    pub fn keys(&self) -> &HashSet<Vec<u8>> {
+        &self.keys
+    }
+
+    pub async fn cancel(self) -> Result<()> {
+        self.events.cancel().await
+    }
+
+    /// Returns why the stream ended, see `WatchEvents::error`.
+    pub fn error(&self) -> Option<&Error> {
+        self.events.error()
+    }
+}
+
+impl Stream for MembershipChanges {
+    type Item = MembershipChange;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            let event = match Pin::new(&mut this.events).poll_next(cx) {
+                Poll::Ready(Some(event)) => event,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let key = event.kv.key;
+            match event.event_type {
+                EventType::Put if !this.keys.contains(&key) => {
+                    this.keys.insert(key.clone());
+                    return Poll::Ready(Some(MembershipChange::Added(key)));
+                }
+                EventType::Delete if this.keys.remove(&key) => {
+                    return Poll::Ready(Some(MembershipChange::Removed(key)));
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,16 +1,30 @@
 //! The Watch API provides an event-based interface for asynchronously monitoring changes to keys.
 
+mod coalesce;
+mod heartbeat;
+mod membership;
+mod reconcile;
+mod resumable;
 mod watch;
 
-pub use watch::{WatchCancelRequest, WatchCreateRequest, WatchResponse};
+pub use coalesce::CoalescedEvents;
+pub use heartbeat::{HeartbeatItem, HeartbeatWatch};
+pub use membership::{MembershipChange, MembershipChanges};
+pub use reconcile::{PrefixWatchEvent, ReconcilingWatch};
+pub use resumable::ResumableWatch;
+pub use watch::{CancelReason, WatchCancelRequest, WatchCreateRequest, WatchResponse};
+
+pub(crate) use watch::progress_request;
 
 use std::{
+    collections::VecDeque,
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
-use futures::Stream;
+use futures::{task::noop_waker_ref, Stream};
 use tokio::sync::mpsc::Sender;
 use tonic::Streaming;
 
@@ -18,6 +32,9 @@ use crate::proto::etcdserverpb;
 use crate::proto::mvccpb;
 use crate::{Error, KeyValue, Result};
 
+/// The `(previous value, new value)` pair of a key, where `None` means the key does not exist.
+pub type KeyTransition = (Option<Vec<u8>>, Option<Vec<u8>>);
+
 pub trait WatchOp {
     fn watch<R>(&self, req: R) -> impl Future<Output = Result<(WatchStream, WatchCanceler)>>
     where
@@ -36,53 +53,302 @@ pub enum WatchInbound {
     Closed,
 }
 
+impl From<Result<WatchResponse>> for WatchInbound {
+    fn from(resp: Result<WatchResponse>) -> Self {
+        match resp {
+            Ok(resp) => WatchInbound::Ready(resp),
+            Err(e) => WatchInbound::Interrupted(e),
+        }
+    }
+}
+
 pub struct WatchStream {
     stream: Streaming<etcdserverpb::WatchResponse>,
+    pending: VecDeque<etcdserverpb::WatchResponse>,
+    watches: usize,
     is_closed: bool,
+    cancel_reason: Option<String>,
+    compact_revision: Option<i64>,
+    created_revision: i64,
+    caught_up: bool,
+    progress_tx: Option<Sender<etcdserverpb::WatchRequest>>,
 }
 
 impl WatchStream {
-    pub(crate) fn new(stream: Streaming<etcdserverpb::WatchResponse>) -> Self {
+    /// Creates the stream of watches created at the revision, starting with the responses
+    /// received while they were being created. While they are not caught up, the sender is used
+    /// to request progress notifications.
+    pub(crate) fn new(
+        stream: Streaming<etcdserverpb::WatchResponse>,
+        pending: VecDeque<etcdserverpb::WatchResponse>,
+        watches: usize,
+        created_revision: i64,
+        caught_up: bool,
+        progress_tx: Sender<etcdserverpb::WatchRequest>,
+    ) -> Self {
         Self {
             stream,
+            pending,
+            watches,
             is_closed: false,
+            cancel_reason: None,
+            compact_revision: None,
+            created_revision,
+            caught_up,
+            progress_tx: (!caught_up).then_some(progress_tx),
         }
     }
 
+    /// Returns why the server canceled the watch, once the stream is closed by a cancellation.
+    /// With several watches, it is the reason of the last one canceled.
+    pub fn cancel_reason(&self) -> Option<&str> {
+        self.cancel_reason.as_deref()
+    }
+
+    /// Returns why the server canceled the watch, parsed, once the stream is closed by a
+    /// cancellation.
+    pub fn cancel_cause(&self) -> Option<CancelReason> {
+        self.cancel_reason
+            .as_deref()
+            .map(|reason| CancelReason::parse(reason, self.compact_revision.unwrap_or(0)))
+    }
+
+    /// Returns the oldest revision still available, once the stream is closed because the
+    /// revisions it was about to deliver are compacted.
+    pub fn compact_revision(&self) -> Option<i64> {
+        self.compact_revision
+    }
+
+    /// Returns whether the historical events replayed from a past `start_revision` have all been
+    /// received, i.e. whether the responses are now live changes.
+    ///
+    /// The replay is over once an event reaches the revision the watch was created at, or the
+    /// server reports progress. Since the last change of the range may be older than that
+    /// revision, progress is requested when the watch is created and after each replayed
+    /// response. A watch without a past `start_revision` is caught up from the start.
+    ///
+    /// Servers before v3.4.24 and v3.5.8 ignore progress requests while a watch is replaying, so
+    /// with them a watch may only be caught up at its next live event or progress notification.
+    pub fn caught_up(&self) -> bool {
+        self.caught_up
+    }
+
+    /// Yields a heartbeat whenever nothing is received from the watch over the interval.
+    pub fn with_heartbeat(self, interval: Duration) -> HeartbeatWatch {
+        HeartbeatWatch::new(self, interval)
+    }
+
+    fn observe(&mut self, resp: &etcdserverpb::WatchResponse) {
+        if resp.canceled {
+            self.watches = self.watches.saturating_sub(1);
+            self.is_closed = self.watches == 0;
+            self.cancel_reason = Some(resp.cancel_reason.clone());
+            if resp.compact_revision > 0 {
+                self.compact_revision = Some(resp.compact_revision);
+            }
+        }
+
+        if !self.caught_up {
+            self.caught_up = ends_replay(resp, self.created_revision);
+            if self.caught_up {
+                self.progress_tx = None;
+            } else if !resp.events.is_empty() {
+                // The replay may be drained, with the last change of the range older than the
+                // revision the watch was created at. If the channel is full, the next replayed
+                // response asks again.
+                if let Some(tx) = &self.progress_tx {
+                    let _ = tx.try_send(progress_request());
+                }
+            }
+        }
+    }
+
+    /// Waits for the next response.
+    ///
+    /// Once the watch is canceled, `Closed` is returned. Of a stream of several watches, the
+    /// cancellations are returned as responses until the last watch is canceled. If the server
+    /// ends the stream without canceling the watches, `Interrupted(Error::WatchEventExhausted)` is
+    /// returned.
     pub async fn inbound(&mut self) -> WatchInbound {
         if self.is_closed {
             return WatchInbound::Closed;
         }
+        if let Some(resp) = self.pending.pop_front() {
+            return self.receive(Some(Ok(resp)));
+        }
 
-        match self.stream.message().await {
-            Ok(Some(resp)) => {
-                if resp.canceled {
-                    self.is_closed = true;
-                }
+        let next = self.stream.message().await.transpose();
+        self.receive(next)
+    }
+
+    /// Returns an already received response without waiting for new ones, as `inbound` would.
+    /// Returns `None` if no response is buffered.
+    pub fn try_next(&mut self) -> Option<WatchInbound> {
+        if self.is_closed {
+            return Some(WatchInbound::Closed);
+        }
+        if let Some(resp) = self.pending.pop_front() {
+            return Some(self.receive(Some(Ok(resp))));
+        }
+
+        let mut cx = Context::from_waker(noop_waker_ref());
+        match Pin::new(&mut self.stream).poll_next(&mut cx) {
+            Poll::Ready(next) => Some(self.receive(next)),
+            Poll::Pending => None,
+        }
+    }
+
+    fn receive(
+        &mut self,
+        next: Option<std::result::Result<etcdserverpb::WatchResponse, tonic::Status>>,
+    ) -> WatchInbound {
+        match next {
+            Some(Ok(resp)) => {
+                self.observe(&resp);
 
-                if resp.canceled && resp.events.is_empty() {
+                if self.is_closed && resp.events.is_empty() {
                     WatchInbound::Closed
                 } else {
-                    WatchInbound::Ready(resp.into())
+                    resp.try_into().into()
                 }
             }
-            Ok(None) => WatchInbound::Interrupted(Error::WatchEventExhausted),
-            Err(e) => WatchInbound::Interrupted(e.into()),
+            Some(Err(e)) => WatchInbound::Interrupted(e.into()),
+            None => WatchInbound::Interrupted(Error::WatchEventExhausted),
         }
     }
 }
 
+/// Returns whether the response shows the replay of a watch created at the revision is over.
+fn ends_replay(resp: &etcdserverpb::WatchResponse, created_revision: i64) -> bool {
+    match resp.events.last() {
+        Some(event) => event
+            .kv
+            .as_ref()
+            .is_some_and(|kv| kv.mod_revision >= created_revision),
+        // An empty response is a progress notification, which is only sent to watches which are
+        // in sync, at the revision they are in sync with.
+        None => {
+            !resp.canceled
+                && resp
+                    .header
+                    .as_ref()
+                    .is_some_and(|header| header.revision >= created_revision)
+        }
+    }
+}
+
+/// The stream yields the same items as `WatchStream::inbound`, and never ends by itself.
 impl Stream for WatchStream {
     type Item = WatchInbound;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::new(&mut self.get_mut().stream)
+        let this = self.get_mut();
+        if this.is_closed {
+            return Poll::Ready(Some(WatchInbound::Closed));
+        }
+        if let Some(resp) = this.pending.pop_front() {
+            return Poll::Ready(Some(this.receive(Some(Ok(resp)))));
+        }
+
+        Pin::new(&mut this.stream)
             .poll_next(cx)
-            .map(|e| match e {
-                Some(Ok(resp)) => Some(WatchInbound::Ready(resp.into())),
-                Some(Err(e)) => Some(WatchInbound::Interrupted(e.into())),
-                None => Some(WatchInbound::Closed),
-            })
+            .map(|next| Some(this.receive(next)))
+    }
+}
+
+/// WatchEvents flattens a watch stream into its individual events.
+///
+/// The stream ends once the watch is canceled by the server or interrupted, after which
+/// `error` tells why.
+pub struct WatchEvents {
+    stream: WatchStream,
+    canceler: WatchCanceler,
+    buffered: VecDeque<Event>,
+    error: Option<Error>,
+    is_closed: bool,
+}
+
+impl WatchEvents {
+    pub(crate) fn new(stream: WatchStream, canceler: WatchCanceler) -> Self {
+        Self {
+            stream,
+            canceler,
+            buffered: VecDeque::new(),
+            error: None,
+            is_closed: false,
+        }
+    }
+
+    /// Returns why the stream ended: `Error::WatchCanceled` if the server canceled the watch,
+    /// e.g. because the revisions it was about to deliver are compacted, or the error which
+    /// interrupted it. Returns `None` while the stream has not ended.
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Takes the error the stream ended with, see `error`.
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+
+    pub async fn cancel(self) -> Result<()> {
+        self.canceler.cancel().await
+    }
+
+    /// Buffers the events over the debounce window, and yields only the latest event of each key.
+    pub fn coalesce_latest(self, debounce: Duration) -> CoalescedEvents {
+        CoalescedEvents::new(self, debounce)
+    }
+
+    /// Yields only the additions and removals of keys, given the keys existing when the watch
+    /// started. See `Client::watch_prefix_membership` to get them consistently.
+    pub fn membership_changes<I>(self, keys: I) -> MembershipChanges
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        MembershipChanges::new(self, keys)
+    }
+
+    fn close(&mut self, error: Error) {
+        self.is_closed = true;
+        self.error.get_or_insert(error);
+    }
+}
+
+impl Stream for WatchEvents {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(event) = this.buffered.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+            if this.is_closed {
+                return Poll::Ready(None);
+            }
+
+            // The canceler is owned until `cancel` consumes it, so any cancellation seen here comes
+            // from the server.
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(WatchInbound::Ready(resp))) => {
+                    if let Some(reason) = resp.cancel_cause() {
+                        this.close(Error::WatchCanceled(reason));
+                    }
+                    this.buffered.extend(resp.events);
+                }
+                Poll::Ready(Some(WatchInbound::Interrupted(e))) => this.close(e),
+                Poll::Ready(Some(WatchInbound::Closed) | None) => {
+                    let reason = this
+                        .stream
+                        .cancel_cause()
+                        .unwrap_or_else(|| CancelReason::Other(String::new()));
+                    this.close(Error::WatchCanceled(reason));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
     }
 }
 
@@ -96,19 +362,42 @@ impl WatchCanceler {
         Self { watch_id, tx }
     }
 
+    /// Returns the ID of the watch, as found in its responses.
+    #[inline]
+    pub fn watch_id(&self) -> i64 {
+        self.watch_id
+    }
+
     pub async fn cancel(self) -> Result<()> {
         self.tx
             .send(WatchCancelRequest::new(self.watch_id).into())
             .await
             .map_err(Error::WatchChannelSend)
     }
+
+    /// Completes once the watch stream has been dropped.
+    pub(crate) async fn closed(&self) {
+        self.tx.closed().await
+    }
 }
 
 /// The kind of event.
 #[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
 pub enum EventType {
     Put,
     Delete,
+    /// An event type unknown to this client, e.g. introduced by a newer etcd version.
+    Unknown(i32),
+}
+
+impl From<i32> for EventType {
+    fn from(value: i32) -> Self {
+        match mvccpb::event::EventType::try_from(value) {
+            Ok(event_type) => event_type.into(),
+            Err(_) => Self::Unknown(value),
+        }
+    }
 }
 
 impl From<mvccpb::event::EventType> for EventType {
@@ -129,15 +418,122 @@ pub struct Event {
     pub prev_kv: Option<KeyValue>,
 }
 
-impl From<mvccpb::Event> for Event {
-    fn from(proto: mvccpb::Event) -> Self {
-        Self {
-            event_type: match proto.r#type {
-                0 => EventType::Put,
-                _ => EventType::Delete, // FIXME: assert valid event type
-            },
-            kv: From::from(proto.kv.expect("must fetch kv")),
+impl Event {
+    /// Whether the event is a put of the key.
+    pub fn is_put(&self) -> bool {
+        self.event_type == EventType::Put
+    }
+
+    /// Whether the event is a deletion of the key.
+    pub fn is_delete(&self) -> bool {
+        self.event_type == EventType::Delete
+    }
+}
+
+/// EventBatch holds the events of one revision, i.e. of one put, delete or transaction, which a
+/// consumer has to apply atomically to mirror the write.
+#[derive(Debug, Clone)]
+pub struct EventBatch {
+    pub revision: i64,
+    pub events: Vec<Event>,
+    /// The previous key-value of each event, in the same order, when watched with `prev_kv`.
+    pub prev_kvs: Vec<Option<KeyValue>>,
+}
+
+impl EventBatch {
+    /// Splits the events of a watch response by revision. etcd does not split the events of one
+    /// revision across responses, but one response can hold several revisions.
+    pub(crate) fn group(events: Vec<Event>) -> Vec<EventBatch> {
+        let mut batches: Vec<EventBatch> = Vec::new();
+        for event in events {
+            let revision = event.kv.mod_revision;
+            let batch = match batches.last_mut() {
+                Some(batch) if batch.revision == revision => batch,
+                _ => {
+                    batches.push(EventBatch {
+                        revision,
+                        events: Vec::new(),
+                        prev_kvs: Vec::new(),
+                    });
+                    batches.last_mut().expect("batch was just pushed")
+                }
+            };
+            batch.prev_kvs.push(event.prev_kv.clone());
+            batch.events.push(event);
+        }
+
+        batches
+    }
+}
+
+impl TryFrom<mvccpb::Event> for Event {
+    type Error = Error;
+
+    fn try_from(proto: mvccpb::Event) -> Result<Self> {
+        Ok(Self {
+            event_type: proto.r#type.into(),
+            kv: From::from(proto.kv.ok_or(Error::MissingField("key-value"))?),
             prev_kv: proto.prev_kv.map(KeyValue::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(header_revision: i64, event_revisions: &[i64]) -> etcdserverpb::WatchResponse {
+        etcdserverpb::WatchResponse {
+            header: Some(etcdserverpb::ResponseHeader {
+                revision: header_revision,
+                ..Default::default()
+            }),
+            events: event_revisions
+                .iter()
+                .map(|revision| mvccpb::Event {
+                    kv: Some(mvccpb::KeyValue {
+                        mod_revision: *revision,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn replay_ends_at_created_revision() {
+        assert!(!ends_replay(&response(100, &[10, 20]), 100));
+        assert!(ends_replay(&response(100, &[90, 100]), 100));
+    }
+
+    #[test]
+    fn replay_of_range_changed_before_head_ends_on_progress() {
+        // The last change of the range is at revision 50 while the watch was created at 100, so
+        // no replayed event reaches the created revision.
+        assert!(!ends_replay(&response(100, &[50]), 100));
+        assert!(ends_replay(&response(100, &[]), 100));
+        assert!(!ends_replay(&response(99, &[]), 100));
+    }
+
+    #[test]
+    fn event_without_key_value_is_an_error() {
+        let resp = etcdserverpb::WatchResponse {
+            header: Some(Default::default()),
+            events: vec![mvccpb::Event::default()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            WatchResponse::try_from(resp),
+            Err(Error::MissingField(_))
+        ));
+    }
+
+    #[test]
+    fn cancellation_does_not_end_replay() {
+        let mut resp = response(100, &[]);
+        resp.canceled = true;
+        assert!(!ends_replay(&resp, 100));
+    }
 }
@@ -0,0 +1,133 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use super::{
+    Event, EventType, WatchCanceler, WatchCreateRequest, WatchInbound, WatchOp, WatchStream,
+};
+use crate::{Client, KeyRange, KeyValue, KeyValueOp, Result};
+
+/// PrefixWatchEvent is an item of a [`ReconcilingWatch`].
+#[derive(Debug, Clone)]
+pub enum PrefixWatchEvent {
+    /// A change of a key under the prefix.
+    Event(Event),
+    /// The watch lost events to a compaction, and the prefix was read again at `revision`.
+    ///
+    /// It is followed by synthetic events turning the last known state into the one read: a put
+    /// for every key created or modified in between, and a delete for every key removed. Changes
+    /// which were overwritten in between are not reported.
+    Reset { revision: i64 },
+}
+
+/// ReconcilingWatch is a watch of a prefix which recovers from compactions.
+///
+/// When the server cancels the watch because the revisions it was about to deliver are
+/// compacted, the prefix is read again at the current revision, the differences are reported,
+/// and the watch resumes right after that revision.
+pub struct ReconcilingWatch {
+    client: Client,
+    key_range: KeyRange,
+    kvs: BTreeMap<Vec<u8>, KeyValue>,
+    pending: VecDeque<PrefixWatchEvent>,
+    stream: WatchStream,
+    canceler: WatchCanceler,
+}
+
+impl ReconcilingWatch {
+    pub(crate) async fn new(client: Client, prefix: Vec<u8>) -> Result<(Vec<KeyValue>, Self)> {
+        let key_range = KeyRange::prefix(prefix);
+        let resp = client.get(key_range.clone()).await?;
+
+        let req = WatchCreateRequest::create(key_range.clone())
+            .start_revision(resp.header.revision() + 1);
+        let (stream, canceler) = client.watch(req).await?;
+
+        let kvs = resp
+            .kvs
+            .iter()
+            .map(|kv| (kv.key.clone(), kv.clone()))
+            .collect();
+
+        Ok((
+            resp.kvs,
+            Self {
+                client,
+                key_range,
+                kvs,
+                pending: VecDeque::new(),
+                stream,
+                canceler,
+            },
+        ))
+    }
+
+    /// Returns the next change, or `None` once the watch is canceled.
+    pub async fn next(&mut self) -> Result<Option<PrefixWatchEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            match self.stream.inbound().await {
+                WatchInbound::Ready(resp) => {
+                    for event in resp.events {
+                        if event.is_delete() {
+                            self.kvs.remove(&event.kv.key);
+                        } else {
+                            self.kvs.insert(event.kv.key.clone(), event.kv.clone());
+                        }
+                        self.pending.push_back(PrefixWatchEvent::Event(event));
+                    }
+                }
+                WatchInbound::Interrupted(e) => return Err(e),
+                WatchInbound::Closed if self.stream.compact_revision().is_some() => {
+                    self.reset().await?;
+                }
+                WatchInbound::Closed => return Ok(None),
+            }
+        }
+    }
+
+    pub async fn cancel(self) -> Result<()> {
+        self.canceler.cancel().await
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        let resp = self.client.get(self.key_range.clone()).await?;
+        let revision = resp.header.revision();
+
+        self.pending.push_back(PrefixWatchEvent::Reset { revision });
+
+        let mut removed = std::mem::take(&mut self.kvs);
+        for kv in resp.kvs {
+            let prev_kv = removed.remove(&kv.key);
+            if prev_kv.as_ref().map(|prev| prev.mod_revision) != Some(kv.mod_revision) {
+                self.pending.push_back(PrefixWatchEvent::Event(Event {
+                    event_type: EventType::Put,
+                    kv: kv.clone(),
+                    prev_kv,
+                }));
+            }
+            self.kvs.insert(kv.key.clone(), kv);
+        }
+        for (key, prev_kv) in removed {
+            // Like the delete events of etcd, the key-value only holds the key and revision.
+            self.pending.push_back(PrefixWatchEvent::Event(Event {
+                event_type: EventType::Delete,
+                kv: KeyValue {
+                    key,
+                    mod_revision: revision,
+                    ..Default::default()
+                },
+                prev_kv: Some(prev_kv),
+            }));
+        }
+
+        let req = WatchCreateRequest::create(self.key_range.clone()).start_revision(revision + 1);
+        let (stream, canceler) = self.client.watch(req).await?;
+
+        self.stream = stream;
+        self.canceler = canceler;
+
+        Ok(())
+    }
+}
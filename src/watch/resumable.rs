@@ -0,0 +1,89 @@
+use super::{
+    CancelReason, WatchCanceler, WatchCreateRequest, WatchInbound, WatchOp, WatchResponse,
+    WatchStream,
+};
+use crate::{Client, Error, KeyRange, Result};
+
+/// ResumableWatch is a watch which survives the expiry of the authentication token.
+///
+/// When the server stops the watch because the token is no longer valid, the token is refreshed
+/// and the watch is created again right after the last revision received, so no event is missed.
+pub struct ResumableWatch {
+    client: Client,
+    key_range: KeyRange,
+    next_revision: i64,
+    stream: WatchStream,
+    canceler: WatchCanceler,
+}
+
+impl ResumableWatch {
+    pub(crate) async fn new(
+        client: Client,
+        key_range: KeyRange,
+        start_revision: i64,
+    ) -> Result<Self> {
+        let next_revision = match start_revision {
+            0 => client.current_revision().await? + 1,
+            revision => revision,
+        };
+
+        let (stream, canceler) = client
+            .watch(WatchCreateRequest::create(key_range.clone()).start_revision(next_revision))
+            .await?;
+
+        Ok(Self {
+            client,
+            key_range,
+            next_revision,
+            stream,
+            canceler,
+        })
+    }
+
+    /// Returns the next response of the watch, or `None` once it is canceled.
+    pub async fn next(&mut self) -> Result<Option<WatchResponse>> {
+        loop {
+            match self.stream.inbound().await {
+                WatchInbound::Ready(resp) => {
+                    if let Some(event) = resp.events.last() {
+                        self.next_revision = event.kv.mod_revision + 1;
+                    }
+                    // The events delivered along with the cancellation are still returned.
+                    if resp.canceled && self.is_auth_failure() {
+                        self.resume().await?;
+                    }
+                    return Ok(Some(resp));
+                }
+                WatchInbound::Interrupted(Error::Unauthenticated(_)) => {}
+                WatchInbound::Interrupted(e) => return Err(e),
+                WatchInbound::Closed if self.is_auth_failure() => {}
+                WatchInbound::Closed => return Ok(None),
+            }
+
+            self.resume().await?;
+        }
+    }
+
+    pub async fn cancel(self) -> Result<()> {
+        self.canceler.cancel().await
+    }
+
+    fn is_auth_failure(&self) -> bool {
+        matches!(
+            self.stream.cancel_cause(),
+            Some(CancelReason::AuthTokenExpired | CancelReason::PermissionDenied)
+        )
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        // Creating a watch refreshes the token first.
+        let req =
+            WatchCreateRequest::create(self.key_range.clone()).start_revision(self.next_revision);
+        let (stream, canceler) = self.client.watch(req).await?;
+
+        self.stream = stream;
+        self.canceler = canceler;
+
+        Ok(())
+    }
+}
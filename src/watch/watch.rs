@@ -1,10 +1,11 @@
 use crate::proto::etcdserverpb;
 use crate::proto::etcdserverpb::watch_request::RequestUnion;
-use crate::{Event, KeyRange, ResponseHeader};
+use crate::{Error, Event, KeyRange, ResponseHeader, Result};
 
 #[derive(Debug)]
 pub struct WatchCreateRequest {
     proto: crate::proto::etcdserverpb::WatchCreateRequest,
+    require_leader: bool,
 }
 
 impl WatchCreateRequest {
@@ -16,11 +17,12 @@ impl WatchCreateRequest {
                 range_end: key_range.range_end,
                 start_revision: 0,
                 progress_notify: false,
-                filters: vec![], // TODO support filters
+                filters: vec![],
                 prev_kv: false,
                 fragment: false, // TODO support fragment
-                watch_id: 0,     // TODO support watch_id
+                watch_id: 0,
             },
+            require_leader: true,
         }
     }
 
@@ -30,16 +32,58 @@ impl WatchCreateRequest {
         self
     }
 
+    /// Sets whether the watch is canceled while the member it is served by has no leader, which
+    /// is the default.
+    ///
+    /// A watch which does not require a leader survives leader elections, but while the member is
+    /// partitioned from the leader it silently stops receiving events, so it may serve stale data.
+    /// Watches sharing a stream require a leader if any of them does.
+    pub fn require_leader(mut self, require: bool) -> Self {
+        self.require_leader = require;
+        self
+    }
+
+    pub(crate) fn requires_leader(&self) -> bool {
+        self.require_leader
+    }
+
+    pub(crate) fn replays_from(&self) -> i64 {
+        self.proto.start_revision
+    }
+
     pub fn progress_notify(mut self) -> Self {
         self.proto.progress_notify = true;
         self
     }
 
+    /// Filters out put events, e.g. to only be notified of deletions.
+    pub fn no_put(mut self) -> Self {
+        self.proto
+            .filters
+            .push(etcdserverpb::watch_create_request::FilterType::Noput as i32);
+        self
+    }
+
+    /// Filters out delete events.
+    pub fn no_delete(mut self) -> Self {
+        self.proto
+            .filters
+            .push(etcdserverpb::watch_create_request::FilterType::Nodelete as i32);
+        self
+    }
+
     /// Sets previous key value.
     pub fn prev_kv(mut self) -> Self {
         self.proto.prev_kv = true;
         self
     }
+
+    /// Sets the ID of the watch instead of letting etcd assign one.
+    /// The ID must not be in use by another watch on the same stream.
+    pub fn watch_id(mut self, watch_id: i64) -> Self {
+        self.proto.watch_id = watch_id;
+        self
+    }
 }
 
 impl From<WatchCreateRequest> for etcdserverpb::WatchCreateRequest {
@@ -56,6 +100,16 @@ impl From<WatchCreateRequest> for etcdserverpb::WatchRequest {
     }
 }
 
+/// Returns a request for a progress notification of the watches of the stream, which the server
+/// sends once they are all in sync.
+pub(crate) fn progress_request() -> etcdserverpb::WatchRequest {
+    etcdserverpb::WatchRequest {
+        request_union: Some(RequestUnion::ProgressRequest(
+            etcdserverpb::WatchProgressRequest {},
+        )),
+    }
+}
+
 impl From<KeyRange> for WatchCreateRequest {
     fn from(key_range: KeyRange) -> Self {
         Self::create(key_range)
@@ -102,17 +156,74 @@ pub struct WatchResponse {
     pub watch_id: i64,
     pub created: bool,
     pub canceled: bool,
+    /// Why the server canceled the watch, if it did.
+    pub cancel_reason: String,
+    /// The oldest revision still available, if the watch was canceled because the revisions it
+    /// was about to deliver are compacted.
+    pub compact_revision: i64,
     pub events: Vec<Event>,
 }
 
-impl From<etcdserverpb::WatchResponse> for WatchResponse {
-    fn from(proto: etcdserverpb::WatchResponse) -> Self {
-        Self {
-            header: From::from(proto.header.expect("must fetch header")),
+impl WatchResponse {
+    /// Returns why the server canceled the watch, parsed, if it did.
+    pub fn cancel_cause(&self) -> Option<CancelReason> {
+        self.canceled
+            .then(|| CancelReason::parse(&self.cancel_reason, self.compact_revision))
+    }
+}
+
+impl TryFrom<etcdserverpb::WatchResponse> for WatchResponse {
+    type Error = Error;
+
+    fn try_from(proto: etcdserverpb::WatchResponse) -> Result<Self> {
+        Ok(Self {
+            header: ResponseHeader::required(proto.header)?,
             watch_id: proto.watch_id,
             created: proto.created,
             canceled: proto.canceled,
-            events: proto.events.into_iter().map(From::from).collect(),
+            cancel_reason: proto.cancel_reason,
+            compact_revision: proto.compact_revision,
+            events: proto
+                .events
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Why the server canceled a watch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CancelReason {
+    /// The revisions the watch was about to deliver are compacted. The watch can only be resumed
+    /// from `revision`, the oldest revision still available, after reading the keys again.
+    Compacted { revision: i64 },
+    /// The authentication token expired or is invalid. The watch can be resumed once the token is
+    /// refreshed.
+    AuthTokenExpired,
+    /// The user is not allowed to watch the keys.
+    PermissionDenied,
+    /// The member serving the watch lost its leader, and the watch requires one.
+    NoLeader,
+    /// Any other reason, as reported by the server.
+    Other(String),
+}
+
+impl CancelReason {
+    pub(crate) fn parse(reason: &str, compact_revision: i64) -> Self {
+        if compact_revision > 0 {
+            CancelReason::Compacted {
+                revision: compact_revision,
+            }
+        } else if reason.contains("auth token") {
+            CancelReason::AuthTokenExpired
+        } else if reason.contains("permission denied") {
+            CancelReason::PermissionDenied
+        } else if reason.contains("no leader") {
+            CancelReason::NoLeader
+        } else {
+            CancelReason::Other(reason.to_owned())
         }
     }
 }
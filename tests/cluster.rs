@@ -0,0 +1,27 @@
+mod common;
+
+use ya_etcd_rs::{ClusterOp, MemberAddRequest, MemberRemoveRequest};
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn member_add_returns_new_member_among_members() {
+    let cli = common::connect().await;
+
+    // A learner which never starts does not count towards the quorum.
+    let resp = cli
+        .member_add(MemberAddRequest::new(
+            vec!["http://127.0.0.1:42380".to_owned()],
+            true,
+        ))
+        .await
+        .unwrap();
+    let id = resp.member.id;
+    cli.member_remove(MemberRemoveRequest::new(id))
+        .await
+        .unwrap();
+
+    assert_ne!(id, 0);
+    assert!(resp.member.is_learner);
+    assert_eq!(resp.member.peer_urls, ["http://127.0.0.1:42380"]);
+    assert!(resp.members.iter().any(|member| member.id == id));
+}
@@ -0,0 +1,23 @@
+#![allow(dead_code)]
+
+use ya_etcd_rs::{Client, ClientConfig};
+
+/// Connects to the cluster set up by `make setup-etcd-cluster`.
+pub async fn connect() -> Client {
+    Client::new(ClientConfig::new([
+        "http://127.0.0.1:12379".into(),
+        "http://127.0.0.1:22379".into(),
+        "http://127.0.0.1:32379".into(),
+    ]))
+    .await
+    .expect("connect to etcd")
+}
+
+/// Returns a key prefix unique to the test, so tests do not see each other's keys.
+pub fn prefix(test: &str) -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("/ya-etcd-rs-test/{test}/{nanos}/")
+}
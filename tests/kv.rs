@@ -0,0 +1,218 @@
+mod common;
+
+use std::time::Duration;
+
+use ya_etcd_rs::{
+    CompactRequest, Error, KeyRange, KeyValueOp, LeaseGrantRequest, LeaseId, LeaseOp,
+    LeaseRevokeRequest, PutRequest, RangeRequest, TxnCmp, TxnCompare, TxnRequest,
+};
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn increment_fails_on_overflow() {
+    let cli = common::connect().await;
+    let key = format!("{}counter", common::prefix("increment"));
+
+    cli.put((key.clone(), i64::MAX.to_string())).await.unwrap();
+    assert!(matches!(
+        cli.increment(key.clone(), 1).await,
+        Err(Error::CounterOverflow(_))
+    ));
+    assert_eq!(cli.increment(key.clone(), -1).await.unwrap(), i64::MAX - 1);
+
+    let resp = cli.get(key.as_str()).await.unwrap();
+    assert_eq!(resp.kvs[0].value_str(), (i64::MAX - 1).to_string());
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn rename_to_same_key_keeps_it() {
+    let cli = common::connect().await;
+    let key = format!("{}key", common::prefix("rename"));
+
+    assert!(!cli.rename(key.clone(), key.clone()).await.unwrap());
+
+    let revision = cli.put((key.clone(), "v")).await.unwrap().header.revision();
+    assert!(cli.rename(key.clone(), key.clone()).await.unwrap());
+
+    let resp = cli.get(key.as_str()).await.unwrap();
+    assert_eq!(resp.kvs[0].value_str(), "v");
+    assert_eq!(resp.kvs[0].mod_revision, revision);
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn swap_with_same_key_changes_nothing() {
+    let cli = common::connect().await;
+    let key = format!("{}key", common::prefix("swap"));
+
+    let revision = cli.put((key.clone(), "v")).await.unwrap().header.revision();
+    assert!(cli.swap(key.clone(), key.clone()).await.unwrap());
+
+    let resp = cli.get(key.as_str()).await.unwrap();
+    assert_eq!(resp.kvs[0].value_str(), "v");
+    assert_eq!(resp.kvs[0].mod_revision, revision);
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn compact_revision_is_last_compaction() {
+    let cli = common::connect().await;
+    let key = format!("{}key", common::prefix("compact_revision"));
+
+    let revision = cli.put((key.clone(), "v")).await.unwrap().header.revision();
+    cli.put((key, "w")).await.unwrap();
+    cli.compact(CompactRequest::new(revision)).await.unwrap();
+
+    assert_eq!(cli.compact_revision().await.unwrap(), revision);
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn response_header_identifies_cluster_and_member() {
+    let cli = common::connect().await;
+    let key = format!("{}key", common::prefix("header"));
+
+    let put = cli.put((key.clone(), "v")).await.unwrap();
+    assert_ne!(put.header.cluster_id(), 0);
+    assert_ne!(put.header.member_id(), 0);
+    assert_ne!(put.header.raft_term(), 0);
+
+    let get = cli.get(key.as_str()).await.unwrap();
+    assert_eq!(get.header.cluster_id(), put.header.cluster_id());
+    assert!(get.header.revision() >= put.header.revision());
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn when_all_requires_every_compare_of_one_key() {
+    let cli = common::connect().await;
+    let key = format!("{}lock", common::prefix("when_all"));
+    let lease_id = cli
+        .grant_lease(LeaseGrantRequest::new(Duration::from_secs(30)))
+        .await
+        .unwrap()
+        .id;
+    cli.put(PutRequest::new(key.clone(), "holder").lease(lease_id))
+        .await
+        .unwrap();
+
+    let fenced = |lease: LeaseId| {
+        TxnRequest::new()
+            .when_all([
+                TxnCompare::value(KeyRange::key(key.as_str()), TxnCmp::Equal, "holder"),
+                TxnCompare::lease(KeyRange::key(key.as_str()), TxnCmp::Equal, lease),
+            ])
+            .and_then(PutRequest::new(key.clone(), "fenced").lease(lease))
+    };
+
+    // The value matches but the lease does not.
+    assert!(!cli.txn(fenced(lease_id + 1)).await.unwrap().succeeded);
+    assert_eq!(
+        cli.get(key.as_str()).await.unwrap().kvs[0].value_str(),
+        "holder"
+    );
+
+    assert!(cli.txn(fenced(lease_id)).await.unwrap().succeeded);
+    assert_eq!(
+        cli.get(key.as_str()).await.unwrap().kvs[0].value_str(),
+        "fenced"
+    );
+
+    cli.revoke(LeaseRevokeRequest::new(lease_id)).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn failed_cas_returns_current_value_for_next_attempt() {
+    let cli = common::connect().await;
+    let key = format!("{}key", common::prefix("cas"));
+    let written = cli
+        .put((key.clone(), "current"))
+        .await
+        .unwrap()
+        .header
+        .revision();
+
+    let cas = |mod_revision: i64, value: &str| {
+        TxnRequest::new()
+            .when(TxnCompare::mod_revision(
+                KeyRange::key(key.as_str()),
+                TxnCmp::Equal,
+                mod_revision,
+            ))
+            .and_then(PutRequest::new(key.clone(), value))
+            .or_else(RangeRequest::new(KeyRange::key(key.as_str())))
+    };
+
+    // A stale revision fails, and the else-branch reads the current key-value.
+    let resp = cli.txn(cas(written - 1, "next")).await.unwrap();
+    assert!(!resp.succeeded);
+    let current = resp.range_kvs().next().unwrap().clone();
+    assert_eq!(current.value_str(), "current");
+    assert_eq!(current.mod_revision, written);
+
+    let resp = cli.txn(cas(current.mod_revision, "next")).await.unwrap();
+    assert!(resp.succeeded);
+    assert_eq!(
+        cli.get(key.as_str()).await.unwrap().kvs[0].value_str(),
+        "next"
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn empty_value_exists_unlike_deleted_key() {
+    let cli = common::connect().await;
+    let key = format!("{}key", common::prefix("empty_value"));
+
+    assert!(!cli.exists(key.clone()).await.unwrap());
+
+    cli.put((key.clone(), "")).await.unwrap();
+    assert!(cli.exists(key.clone()).await.unwrap());
+    let resp = cli.get(key.as_str()).await.unwrap();
+    assert_eq!(resp.kvs.len(), 1);
+    assert_eq!(&resp.kvs[0].value[..], b"");
+
+    cli.delete(key.as_str()).await.unwrap();
+    assert!(!cli.exists(key.clone()).await.unwrap());
+    assert!(cli.get(key.as_str()).await.unwrap().kvs.is_empty());
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn changes_since_includes_deletions() {
+    let cli = common::connect().await;
+    let prefix = common::prefix("changes_since");
+    let key = |name: &str| format!("{prefix}{name}");
+
+    cli.put((key("deleted"), "v")).await.unwrap();
+    cli.put((key("recreated"), "v")).await.unwrap();
+    let since = cli
+        .put((key("unchanged"), "v"))
+        .await
+        .unwrap()
+        .header
+        .revision();
+
+    cli.delete(KeyRange::key(key("deleted"))).await.unwrap();
+    cli.delete(KeyRange::key(key("recreated"))).await.unwrap();
+    cli.put((key("added"), "v")).await.unwrap();
+    cli.put((key("recreated"), "v2")).await.unwrap();
+
+    let changes: Vec<_> = cli
+        .changes_since(prefix.as_str(), since)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|event| (event.is_delete(), event.kv.key_str().to_owned()))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (true, key("deleted")),
+            (false, key("added")),
+            (false, key("recreated")),
+        ]
+    );
+}
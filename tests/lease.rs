@@ -0,0 +1,67 @@
+mod common;
+
+use std::time::Duration;
+
+use ya_etcd_rs::{KeyValueOp, LeaseGrantRequest, LeaseOp, LeaseRevokeRequest, PutRequest};
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn keep_alive_many_refreshes_in_background() {
+    let cli = common::connect().await;
+    let short = cli
+        .grant_lease(LeaseGrantRequest::new(Duration::from_secs(2)))
+        .await
+        .unwrap()
+        .id;
+    let long = cli
+        .grant_lease(LeaseGrantRequest::new(Duration::from_secs(10)))
+        .await
+        .unwrap()
+        .id;
+
+    let mut keep_alive = cli
+        .keep_alive_many(vec![short, long])
+        .await
+        .unwrap()
+        .spawn();
+
+    // The short lease outlives its TTL only if it is refreshed.
+    tokio::time::sleep(Duration::from_secs(4)).await;
+    assert!(keep_alive.ttl(short).is_some_and(|ttl| ttl > 0));
+    assert!(keep_alive.ttl(long).is_some_and(|ttl| ttl > 0));
+    assert!(keep_alive.expired().is_empty());
+
+    cli.revoke(LeaseRevokeRequest::new(short)).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while keep_alive.expired().is_empty() {
+            keep_alive.changed().await.unwrap();
+        }
+    })
+    .await
+    .expect("revoked lease is reported expired");
+    assert_eq!(keep_alive.expired(), [short]);
+
+    keep_alive.revoke().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn revoke_keep_alive_deletes_attached_keys() {
+    let cli = common::connect().await;
+    let key = format!("{}key", common::prefix("revoke_keep_alive"));
+    let lease_id = cli
+        .grant_lease(LeaseGrantRequest::new(Duration::from_secs(30)))
+        .await
+        .unwrap()
+        .id;
+    cli.put(PutRequest::new(key.clone(), "v").lease(lease_id))
+        .await
+        .unwrap();
+
+    let mut keep_alive = cli.keep_alive_for(lease_id).await.unwrap();
+    keep_alive.keep_alive().await.unwrap();
+    keep_alive.revoke().await.unwrap();
+
+    assert!(cli.get(key.as_str()).await.unwrap().kvs.is_empty());
+    assert!(cli.lease_keys(lease_id).await.unwrap().is_empty());
+}
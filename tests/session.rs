@@ -0,0 +1,27 @@
+mod common;
+
+use std::time::Duration;
+
+use ya_etcd_rs::{Error, LeaseOp, LeaseRevokeRequest};
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn session_reports_lost_lease() {
+    let cli = common::connect().await;
+    let session = cli.session(Duration::from_secs(3)).await.unwrap();
+    assert!(!session.state().unwrap().expired);
+
+    cli.revoke(LeaseRevokeRequest::new(session.lease_id()))
+        .await
+        .unwrap();
+    tokio::time::timeout(Duration::from_secs(5), session.expired())
+        .await
+        .expect("session expires");
+
+    let state = session.detach().unwrap();
+    assert!(state.expired);
+    assert!(matches!(
+        cli.resume_session(state).await,
+        Err(Error::SessionExpired(_))
+    ));
+}
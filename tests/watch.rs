@@ -0,0 +1,119 @@
+mod common;
+
+use std::time::Duration;
+
+use ya_etcd_rs::{KeyRange, KeyValueOp, WatchCreateRequest, WatchInbound, WatchOp};
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn watch_from_past_revision_catches_up_when_range_changed_before_head() {
+    let cli = common::connect().await;
+    let prefix = common::prefix("caught_up");
+    let key = format!("{prefix}watched");
+
+    let start = cli.put((key.clone(), "v")).await.unwrap().header.revision();
+    // Move the head past the last change of the watched key.
+    for i in 0..3 {
+        cli.put((format!("{prefix}other-{i}"), "v")).await.unwrap();
+    }
+
+    let (mut stream, canceler) = cli
+        .watch(WatchCreateRequest::create(KeyRange::key(key.as_str())).start_revision(start))
+        .await
+        .unwrap();
+    assert!(!stream.caught_up());
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !stream.caught_up() {
+            match stream.inbound().await {
+                WatchInbound::Ready(_) => {}
+                other => panic!("watch ended before catching up: {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("watch catches up");
+
+    canceler.cancel().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn watch_ranges_receives_events_between_creates() {
+    let cli = common::connect().await;
+    let prefix = common::prefix("watch_ranges_writes");
+    let key = format!("{prefix}0/key");
+
+    // Keep writing to the first range so that it has events before the other watches are created.
+    let writer = {
+        let cli = cli.clone();
+        let key = key.clone();
+        tokio::spawn(async move {
+            for i in 0.. {
+                cli.put((key.clone(), i.to_string())).await.unwrap();
+            }
+        })
+    };
+
+    for _ in 0..20 {
+        let ranges = (0..16)
+            .map(|i| KeyRange::prefix(format!("{prefix}{i}/")))
+            .collect();
+        let (mut stream, cancelers) = cli.watch_ranges(ranges).await.unwrap();
+
+        let first = cancelers[0].watch_id();
+        let resp = tokio::time::timeout(Duration::from_secs(5), stream.inbound())
+            .await
+            .expect("event of the first range");
+        match resp {
+            WatchInbound::Ready(resp) => {
+                assert_eq!(resp.watch_id, first);
+                assert!(!resp.events.is_empty());
+            }
+            other => panic!("unexpected response: {other:?}"),
+        }
+
+        for canceler in cancelers {
+            canceler.cancel().await.unwrap();
+        }
+    }
+
+    writer.abort();
+}
+
+#[tokio::test]
+#[ignore = "requires a running etcd cluster"]
+async fn watch_ranges_closes_after_last_watch_is_canceled() {
+    let cli = common::connect().await;
+    let prefix = common::prefix("watch_ranges_cancel");
+    let (a, b) = (format!("{prefix}a"), format!("{prefix}b"));
+
+    let (mut stream, mut cancelers) = cli
+        .watch_ranges(vec![KeyRange::key(a.as_str()), KeyRange::key(b.as_str())])
+        .await
+        .unwrap();
+    let canceler_b = cancelers.pop().unwrap();
+    let canceler_a = cancelers.pop().unwrap();
+    let (id_a, id_b) = (canceler_a.watch_id(), canceler_b.watch_id());
+
+    canceler_a.cancel().await.unwrap();
+    match stream.inbound().await {
+        WatchInbound::Ready(resp) => {
+            assert!(resp.canceled);
+            assert_eq!(resp.watch_id, id_a);
+        }
+        other => panic!("expected the cancellation of the first watch: {other:?}"),
+    }
+
+    cli.put((b.clone(), "v")).await.unwrap();
+    match stream.inbound().await {
+        WatchInbound::Ready(resp) => {
+            assert_eq!(resp.watch_id, id_b);
+            assert_eq!(resp.events[0].kv.key_str(), b);
+        }
+        other => panic!("expected an event of the second watch: {other:?}"),
+    }
+
+    canceler_b.cancel().await.unwrap();
+    assert!(matches!(stream.inbound().await, WatchInbound::Closed));
+}